@@ -11,7 +11,7 @@ fn main() {
     stdin.read_line(&mut input).unwrap();
     let selector = Selector::new(&input);
 
-    write!(stdout, "HTML document:\n").unwrap();
+    writeln!(stdout, "HTML document:").unwrap();
     stdout.flush().unwrap();
     input.clear();
     stdin.read_to_string(&mut input).unwrap();
@@ -20,6 +20,6 @@ fn main() {
     println!("{:#?}", document);
 
     for node in document.select(&selector) {
-        println!("{:?}", node.value());
+        println!("{:?}", node.as_kind());
     }
 }
@@ -12,7 +12,7 @@ fn main() {
     stdin.read_line(&mut input).unwrap();
     let selector = Selector::new(&input);
 
-    write!(stdout, "HTML fragment:\n").unwrap();
+    writeln!(stdout, "HTML fragment:").unwrap();
     stdout.flush().unwrap();
     input.clear();
     stdin.read_to_string(&mut input).unwrap();
@@ -21,6 +21,6 @@ fn main() {
     println!("{:#?}", fragment);
 
     for node in fragment.select(&selector) {
-        println!("{:?}", node.value());
+        println!("{:?}", node.as_kind());
     }
 }
@@ -2,7 +2,10 @@
 
 use std::borrow::Cow;
 
-use ego_tree::{iter::Nodes, Tree};
+use ego_tree::{
+    iter::{Edge, Nodes, Traverse},
+    NodeId, Tree,
+};
 use html5ever::{
     driver, serialize,
     serialize::{SerializeOpts, TraversalScope},
@@ -76,8 +79,43 @@ impl Html {
         HtmlSelect { inner: self.tree.nodes(), selector }
     }
 
+    /// Returns an iterator over elements matching a selector, skipping matches nested inside another match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use htmler::{Html, Selector};
+    /// let html = Html::parse_fragment("<div><div>inner</div></div><div>outer</div>");
+    /// let selector = Selector::new("div");
+    /// assert_eq!(html.select(&selector).count(), 3);
+    /// assert_eq!(html.select_outermost(&selector).count(), 2);
+    /// ```
+    pub fn select_outermost<'a, 'b>(&'a self, selector: &'b Selector) -> HtmlSelectOutermost<'a, 'b> {
+        HtmlSelectOutermost { inner: self.tree.root().traverse(), selector, matched: None }
+    }
+
+    /// Detaches every element matching a selector, together with its subtree. Returns how many were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use htmler::{Html, Selector};
+    /// let mut html = Html::parse_fragment("<p>text</p><div class=\"ad\"><p>ad</p></div>");
+    /// assert_eq!(html.remove_matches(&Selector::new(".ad")), 1);
+    /// assert_eq!(html.select(&Selector::new("p")).count(), 1);
+    /// ```
+    pub fn remove_matches(&mut self, selector: &Selector) -> usize {
+        let ids: Vec<NodeId> = self.select_outermost(selector).map(|node| node.ptr.id()).collect();
+        for id in &ids {
+            if let Some(mut node) = self.tree.get_mut(*id) {
+                node.detach();
+            }
+        }
+        ids.len()
+    }
+
     /// Returns the root `<html>` element.
-    pub fn root_node(&self) -> Node {
+    pub fn root_node(&self) -> Node<'_> {
         let root_node = self.tree.root().children().find(|child| child.value().is_element()).expect("html node missing");
         Node::wrap(root_node).unwrap()
     }
@@ -107,13 +145,14 @@ impl<'a, 'b> Iterator for HtmlSelect<'a, 'b> {
 
     fn next(&mut self) -> Option<Node<'a>> {
         for node in self.inner.by_ref() {
-            match Node::wrap(node) {
-                Some(element) => {
-                    if element.ptr.parent().is_some() && self.selector.matches(&element) {
-                        return Some(element);
-                    }
+            if let Some(element) = Node::wrap(node) {
+                // detached nodes stay in the arena, their topmost ancestor is not the document
+                if element.ptr.parent().is_some()
+                    && self.selector.matches(&element)
+                    && element.ptr.ancestors().last().is_some_and(|top| !top.value().is_element())
+                {
+                    return Some(element);
                 }
-                None => {}
             }
         }
         None
@@ -138,6 +177,36 @@ impl<'a, 'b> DoubleEndedIterator for HtmlSelect<'a, 'b> {
     }
 }
 
+/// Iterator over outermost elements matching a selector.
+#[derive(Debug)]
+pub struct HtmlSelectOutermost<'a, 'b> {
+    inner: Traverse<'a, NodeKind>,
+    selector: &'b Selector,
+    matched: Option<NodeId>,
+}
+
+impl<'a, 'b> Iterator for HtmlSelectOutermost<'a, 'b> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        for edge in self.inner.by_ref() {
+            match edge {
+                Edge::Open(node) if self.matched.is_none() => {
+                    if let Some(element) = Node::wrap(node) {
+                        if element.ptr.parent().is_some() && self.selector.matches(&element) {
+                            self.matched = Some(node.id());
+                            return Some(element);
+                        }
+                    }
+                }
+                Edge::Close(node) if self.matched == Some(node.id()) => self.matched = None,
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
 mod serializable;
 mod tree_sink;
 
@@ -145,13 +214,29 @@ mod tree_sink;
 mod tests {
     use super::{Html, Selector};
 
+    #[test]
+    fn inner_text_of_nested_markup() {
+        let html = Html::parse_fragment("<p>a<b>b</b><!-- comment -->c</p>");
+        let p = html.select(&Selector::try_parse("p").unwrap()).next().unwrap();
+        assert_eq!(p.text().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(p.inner_text(), "abc");
+    }
+
+    #[test]
+    fn remove_matches_prunes_subtrees() {
+        let mut html = Html::parse_document("<div><p>keep</p><div class=\"ad\"><div class=\"ad\">a</div>b</div></div>");
+        assert_eq!(html.remove_matches(&Selector::new(".ad")), 1);
+        assert_eq!(html.root_node().inner_text(), "keep");
+        assert_eq!(html.select(&Selector::new("div")).count(), 1);
+    }
+
     #[test]
     fn root_element_fragment() {
         let html = Html::parse_fragment(r#"<a href="http://github.com">1</a>"#);
         let root_ref = html.root_node();
         let href = root_ref.select(&Selector::try_parse("a").unwrap()).next().unwrap();
         assert_eq!(href.inner_html(), "1");
-        assert_eq!(href.value().get_attribute("href").unwrap(), "http://github.com");
+        assert_eq!(href.as_data().unwrap().get_attribute("href").unwrap(), "http://github.com");
     }
 
     #[test]
@@ -187,6 +272,15 @@ mod tests {
         assert_eq!(upper, Some(10));
     }
 
+    #[test]
+    fn select_outermost_skips_nested() {
+        let html =
+            Html::parse_fragment(r#"<div class="quote">a<div class="quote">b</div></div><p><span class="quote">c</span></p>"#);
+        let selector = Selector::try_parse(".quote").unwrap();
+        let result: Vec<_> = html.select_outermost(&selector).map(|e| e.text().collect::<String>()).collect();
+        assert_eq!(result, vec!["ab", "c"]);
+    }
+
     #[cfg(feature = "atomic")]
     #[test]
     fn html_is_send() {
@@ -33,7 +33,7 @@ impl TreeSink for Html {
     // What is the name of this element?
     //
     // Should never be called on a non-element node; feel free to panic!.
-    fn elem_name(&self, target: &Self::Handle) -> ExpandedName {
+    fn elem_name(&self, target: &Self::Handle) -> ExpandedName<'_> {
         match self.tree.get(*target).unwrap().value() {
             NodeKind::Element(e) => e.name.expanded(),
             _ => panic!("not an element"),
@@ -82,7 +82,7 @@ impl TreeSink for Html {
 
             NodeOrText::AppendText(text) => {
                 let text = make_tendril(text);
-                let can_concat = parent.last_child().map_or(false, |mut n| n.value().is_text());
+                let can_concat = parent.last_child().is_some_and(|mut n| n.value().is_text());
 
                 if can_concat {
                     let mut last_child = parent.last_child().unwrap();
@@ -166,7 +166,7 @@ impl TreeSink for Html {
 
                 NodeOrText::AppendText(text) => {
                     let text = make_tendril(text);
-                    let can_concat = sibling.prev_sibling().map_or(false, |mut n| n.value().is_text());
+                    let can_concat = sibling.prev_sibling().is_some_and(|mut n| n.value().is_text());
 
                     if can_concat {
                         let mut prev_sibling = sibling.prev_sibling().unwrap();
@@ -1,4 +1,3 @@
-#![feature(once_cell)]
 #![doc = include_str!("../readme.md")]
 #![warn(
     missing_docs,
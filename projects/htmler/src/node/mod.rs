@@ -158,7 +158,7 @@ impl NodeData {
     }
 
     /// Returns an iterator over the element's classes.
-    pub fn classes(&self) -> HtmlClasses {
+    pub fn classes(&self) -> HtmlClasses<'_> {
         let classes = self.classes.get_or_init(|| {
             let mut classes: Vec<LocalName> = self
                 .attrs
@@ -189,7 +189,7 @@ impl NodeData {
     }
 
     /// Returns an iterator over the element's attributes.
-    pub fn attributes(&self) -> HtmlAttributes {
+    pub fn attributes(&self) -> HtmlAttributes<'_> {
         HtmlAttributes { inner: self.attrs.iter() }
     }
 }
@@ -40,9 +40,8 @@ pub(crate) fn serialize<S: Serializer>(
                 if node == self_node && traversal_scope == TraversalScope::ChildrenOnly(None) {
                     continue;
                 }
-                match node.value() {
-                    NodeKind::Element(e) => serializer.end_elem(e.name.clone())?,
-                    _ => {}
+                if let NodeKind::Element(e) = node.value() {
+                    serializer.end_elem(e.name.clone())?
                 }
             }
         }
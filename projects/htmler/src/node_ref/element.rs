@@ -120,7 +120,7 @@ impl<'a> selectors::Element for Node<'a> {
     }
 
     fn is_root(&self) -> bool {
-        self.ptr.parent().map_or(false, |parent| parent.value().is_document())
+        self.ptr.parent().is_some_and(|parent| parent.value().is_document())
     }
 }
 
@@ -140,12 +140,12 @@ mod tests {
         let sel = Selector::try_parse("p").unwrap();
 
         let element = fragment.select(&sel).next().unwrap();
-        assert_eq!(true, element.has_id(&CssLocalName::from("link_id_456"), CaseSensitivity::CaseSensitive));
+        assert!(element.has_id(&CssLocalName::from("link_id_456"), CaseSensitivity::CaseSensitive));
 
         let html = "<p>hey there</p>";
         let fragment = Html::parse_fragment(html);
         let element = fragment.select(&sel).next().unwrap();
-        assert_eq!(false, element.has_id(&CssLocalName::from("any_link_id"), CaseSensitivity::CaseSensitive));
+        assert!(!element.has_id(&CssLocalName::from("any_link_id"), CaseSensitivity::CaseSensitive));
     }
 
     #[test]
@@ -154,13 +154,13 @@ mod tests {
         let fragment = Html::parse_fragment(html);
         let sel = Selector::try_parse("link").unwrap();
         let element = fragment.select(&sel).next().unwrap();
-        assert_eq!(true, element.is_link());
+        assert!(element.is_link());
 
         let html = "<p>hey there</p>";
         let fragment = Html::parse_fragment(html);
         let sel = Selector::try_parse("p").unwrap();
         let element = fragment.select(&sel).next().unwrap();
-        assert_eq!(false, element.is_link());
+        assert!(!element.is_link());
     }
 
     #[test]
@@ -169,18 +169,12 @@ mod tests {
         let fragment = Html::parse_fragment(html);
         let sel = Selector::try_parse("p").unwrap();
         let element = fragment.select(&sel).next().unwrap();
-        assert_eq!(
-            true,
-            <Node as Element>::has_class(&element, &CssLocalName::from("my_class"), CaseSensitivity::CaseSensitive)
-        );
+        assert!(<Node as Element>::has_class(&element, &CssLocalName::from("my_class"), CaseSensitivity::CaseSensitive));
 
         let html = "<p>hey there</p>";
         let fragment = Html::parse_fragment(html);
         let sel = Selector::try_parse("p").unwrap();
         let element = fragment.select(&sel).next().unwrap();
-        assert_eq!(
-            false,
-            <Node as Element>::has_class(&element, &CssLocalName::from("my_class"), CaseSensitivity::CaseSensitive)
-        );
+        assert!(!<Node as Element>::has_class(&element, &CssLocalName::from("my_class"), CaseSensitivity::CaseSensitive));
     }
 }
@@ -69,6 +69,10 @@ impl<'a> Node<'a> {
     pub fn text(&self) -> Text<'a> {
         Text { inner: self.ptr.traverse() }
     }
+    /// Returns the concatenated text of all descendent text nodes.
+    pub fn inner_text(&self) -> String {
+        self.text().collect()
+    }
     /// Returns an iterator over descendent elements.
     pub fn children(&self) -> impl Iterator<Item = Node<'a>> {
         self.ptr.children().map(Node::new)
@@ -82,6 +86,16 @@ impl<'a> Node<'a> {
         self.ptr.last_child().map(Node::new)
     }
 
+    /// Returns an iterator over the following siblings.
+    pub fn next_siblings(&self) -> impl Iterator<Item = Node<'a>> {
+        self.ptr.next_siblings().map(Node::new)
+    }
+
+    /// Returns the parent node.
+    pub fn parent(&self) -> Option<Node<'a>> {
+        self.ptr.parent().map(Node::new)
+    }
+
     /// Returns the parent element.
     pub fn descendants(&self) -> impl Iterator<Item = Node<'a>> {
         self.ptr.descendants().map(Node::new)
@@ -28,7 +28,7 @@ impl Selector {
     }
 
     /// Parses a CSS selector group.
-    pub fn try_parse(selectors: &'_ str) -> Result<Self, SelectorErrorKind> {
+    pub fn try_parse(selectors: &'_ str) -> Result<Self, SelectorErrorKind<'_>> {
         let mut parser_input = cssparser::ParserInput::new(selectors);
         let mut parser = cssparser::Parser::new(&mut parser_input);
 
@@ -51,6 +51,27 @@ impl Selector {
     }
 }
 
+/// Serializes the selector group back to CSS, which [`Selector::try_parse`] reads as an equal selector.
+///
+/// # Examples
+///
+/// ```
+/// # use htmler::Selector;
+/// let selector = Selector::new("div.a > p#b, span[lang=\"zh\"]");
+/// assert_eq!(selector.to_string(), "div.a > p#b, span[lang=\"zh\"]");
+/// ```
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, selector) in self.selectors.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            cssparser::ToCss::to_css(selector, f)?;
+        }
+        Ok(())
+    }
+}
+
 /// An implementation of `Parser` for `selectors`
 struct Parser;
 
@@ -100,7 +121,8 @@ impl cssparser::ToCss for CssString {
     where
         W: fmt::Write,
     {
-        cssparser::serialize_string(&self.0, dest)
+        // the attribute selector writes the quotes, only escape the contents
+        fmt::Write::write_str(&mut cssparser::CssStringWriter::new(dest), &self.0)
     }
 }
 
@@ -187,6 +209,14 @@ mod tests {
         let _sel: Selector = (*s).try_into().unwrap();
     }
 
+    #[test]
+    fn display_round_trip() {
+        let selector = Selector::new("div.RichText   p:not(.ad) ,a[href^='https'] + img , ul>li:first-child");
+        let css = selector.to_string();
+        assert_eq!(css, "div.RichText p:not(.ad), a[href^=\"https\"] + img, ul > li:first-child");
+        assert_eq!(Selector::try_parse(&css).unwrap(), selector);
+    }
+
     #[test]
     #[should_panic]
     fn invalid_selector_conversions() {
@@ -0,0 +1,62 @@
+//! Rendering throughput on large synthetic pages, run with `cargo bench --bench render`.
+
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use zhihu_link::{RenderOptions, ZhihuAnswer, ZhihuArticle};
+
+#[path = "../tests/common/fixtures.rs"]
+mod fixtures;
+
+use fixtures::{answer_page, article_page};
+
+/// Prose with inline and display formulas, in the old image and the current span markup.
+fn math_heavy() -> String {
+    let paragraph = r#"<p>设<span class="ztext-math" data-eeimg="1" data-tex="f(x)=\sum_{n=0}^{\infty} a_n x^n">f(x)</span>在<span class="ztext-math" data-tex="|x|&lt;R">|x|</span>内收敛，则</p><p><span class="ztext-math" data-tex="\int_0^x f(t)\,dt = \sum_{n=0}^{\infty} \frac{a_n}{n+1} x^{n+1}">...</span></p><p>旧式公式<img eeimg="1" src="https://www.zhihu.com/equation?tex=e%5E%7Bi%5Cpi%7D%2B1%3D0" alt="e^{i\pi}+1=0">同理。</p>"#;
+    answer_page(&paragraph.repeat(300))
+}
+
+/// Figures with captions, lazy loaded sources and srcsets, between short paragraphs.
+fn image_heavy() -> String {
+    let mut content = String::new();
+    for i in 0..300 {
+        content.push_str(&format!(
+            r#"<p>第{i}张图：</p><figure data-size="normal"><noscript><img src="https://pic1.zhimg.com/v2-{i:04}_b.jpg"></noscript><img src="data:image/svg+xml;utf8,&lt;svg&gt;" data-original="https://pic1.zhimg.com/v2-{i:04}_r.jpg" srcset="https://pic1.zhimg.com/v2-{i:04}_720w.jpg 720w, https://pic1.zhimg.com/v2-{i:04}_1440w.jpg 1440w" data-size="normal" class="origin_image zh-lightbox-thumb lazy"><figcaption>图{i}的说明</figcaption></figure>"#
+        ));
+    }
+    article_page(&content)
+}
+
+/// Nested lists, code blocks, links and tables.
+fn structure_heavy() -> String {
+    let block = r#"<h2>小节</h2><ul><li><p>要点<a href="https://link.zhihu.com/?target=https%3A//example.com/%3Futm_source%3Dzhihu">链接</a></p><ol><li>一</li><li>二<ul><li>细节</li></ul></li></ol></li></ul><pre lang="rust"><code>fn main() {
+    println!("hello");
+}</code></pre><table><thead><tr><th>名称</th><th align="right">数量</th></tr></thead><tbody><tr><td>苹果</td><td>3</td></tr></tbody></table>"#;
+    answer_page(&block.repeat(300))
+}
+
+/// Parses and renders one kind of page, picked per fixture outside the timed loop.
+type Render = fn(&str) -> String;
+
+fn render_answer(html: &str) -> String {
+    ZhihuAnswer::from_html(html, RenderOptions::default()).unwrap().to_string()
+}
+
+fn render_article(html: &str) -> String {
+    ZhihuArticle::from_html(html, RenderOptions::default()).unwrap().to_string()
+}
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    let fixtures: [(&str, String, Render); 3] = [
+        ("math", math_heavy(), render_answer),
+        ("images", image_heavy(), render_article),
+        ("structure", structure_heavy(), render_answer),
+    ];
+    for (name, html, parse) in &fixtures {
+        group.throughput(Throughput::Bytes(html.len() as u64));
+        group.bench_function(*name, |b| b.iter(|| parse(black_box(html))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
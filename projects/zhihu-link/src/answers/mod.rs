@@ -1,27 +1,111 @@
-use crate::{ZhihuError, ZhihuResult};
-use htmler::{Html, Node, NodeKind, Selector};
+use crate::{
+    Comment, ContentKind, CopyrightPolicy, ParseMode, RelatedLink, RenderOptions, UrlDispatcher, ZhihuError, ZhihuResult,
+    renders::{
+        AuthorInfo, Document, Engagement, MarkdownRenderer, Reference, author_info, content_item, content_times,
+        initial_entity, page_title, related_links, render_section, topics, write_document,
+    },
+    utils::{answer_url, reading_minutes},
+};
+#[cfg(feature = "network")]
+use crate::{
+    Progress, ZhihuClient,
+    assets::{AssetReport, localize_images},
+    comments::fetch_comments,
+};
+use htmler::{Html, Node, Selector};
+use serde_json::Value;
 use std::{
-    fmt::{Display, Formatter, Write},
+    fmt::{Display, Formatter},
     io::Write as _,
     path::Path,
     str::FromStr,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ZhihuAnswer {
     title: String,
+    /// The description of the question, rendered to markdown.
+    description: String,
     content: String,
+    /// The html of the rich text, kept to render sections on demand.
+    source: String,
+    permalink: Option<String>,
+    unhandled_tags: Vec<String>,
+    images: Vec<String>,
+    related: Vec<RelatedLink>,
+    references: Vec<Reference>,
+    engagement: Engagement,
+    author: Option<String>,
+    author_info: Option<AuthorInfo>,
+    topics: Vec<String>,
+    published: Option<String>,
+    edited: Option<String>,
+    deleted: bool,
+    /// When the page was fetched, in seconds since the unix epoch, unknown for html parsed from elsewhere.
+    fetched_at: Option<u64>,
+    repost_forbidden: bool,
+    comment_permission: Option<String>,
+    comments_closed: bool,
+    comments: Vec<Comment>,
+    options: RenderOptions,
 }
 
 impl Default for ZhihuAnswer {
     fn default() -> Self {
-        Self { title: "".to_string(), content: "".to_string() }
+        Self {
+            title: "".to_string(),
+            description: "".to_string(),
+            content: "".to_string(),
+            source: "".to_string(),
+            permalink: None,
+            unhandled_tags: vec![],
+            images: vec![],
+            related: vec![],
+            references: vec![],
+            engagement: Engagement::default(),
+            author: None,
+            author_info: None,
+            topics: vec![],
+            published: None,
+            edited: None,
+            deleted: false,
+            fetched_at: None,
+            repost_forbidden: false,
+            comment_permission: None,
+            comments_closed: false,
+            comments: vec![],
+            options: RenderOptions::default(),
+        }
     }
 }
 
 impl Display for ZhihuAnswer {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "# {}\n\n{}", self.title, self.content)
+        let byline = match (&self.author, &self.author_info) {
+            _ if self.deleted => Some("该回答已被删除".to_string()),
+            (_, Some(info)) if self.options.author_byline => Some(info.byline()),
+            (Some(author), _) if self.options.author_byline => Some(format!("作者: {}", author)),
+            _ => None,
+        };
+        let document = Document {
+            title: &self.title,
+            intro: self.options.question_description.then_some(self.description.as_str()),
+            byline: byline.as_deref(),
+            author: self.author.as_deref(),
+            topics: &self.topics,
+            content: &self.content,
+            permalink: self.permalink.as_deref(),
+            related: &self.related,
+            references: &self.references,
+            column: None,
+            engagement: self.engagement,
+            published: self.published.as_deref(),
+            edited: self.edited.as_deref(),
+            fetched_at: self.fetched_at,
+            repost_forbidden: self.repost_forbidden,
+            comments: &self.comments,
+        };
+        write_document(f, &self.options, &document)
     }
 }
 
@@ -29,138 +113,370 @@ impl FromStr for ZhihuAnswer {
     type Err = ZhihuError;
 
     fn from_str(html: &str) -> Result<Self, Self::Err> {
-        let mut empty = Self::default();
-        empty.do_parse(html)?;
-        Ok(empty)
+        Self::from_html(html, RenderOptions::default())
     }
 }
 
 impl ZhihuAnswer {
+    #[cfg(feature = "network")]
     /// 通过问题 ID 和回答 ID 获取知乎回答, 并渲染为 markdown
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// # use zhihu_link::ZhihuAnswer;
+    /// # async fn run() -> zhihu_link::ZhihuResult<()> {
     /// let answer = ZhihuAnswer::new(58151047, 1).await?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub async fn new(question: usize, answer: usize) -> ZhihuResult<Self> {
-        let html = Self::request(question, answer).await?;
-        Ok(html.parse()?)
+        Self::with_client(&ZhihuClient::default(), question, answer).await
     }
+    #[cfg(feature = "network")]
+    /// 使用给定的客户端获取知乎回答, 以便控制请求头, 代理或 cookie
+    pub async fn with_client(client: &ZhihuClient, question: usize, answer: usize) -> ZhihuResult<Self> {
+        let html = client.get_text(&answer_url(question, answer)).await?;
+        let mut out: Self = html.parse()?;
+        out.permalink.get_or_insert_with(|| answer_url(question, answer));
+        Ok(out)
+    }
+    /// 解析知乎回答的 html, 并按照给定的选项渲染为 markdown
+    pub fn from_html(html: &str, options: RenderOptions) -> ZhihuResult<Self> {
+        let mut empty = Self { options, ..Self::default() };
+        empty.do_parse(html)?;
+        Ok(empty)
+    }
+    /// 设置抓取页面的时间, 以 unix 秒计, 开启 [`RenderOptions::provenance`] 时写入来源注释
+    pub fn with_fetched_at(mut self, fetched_at: Option<u64>) -> Self {
+        self.fetched_at = fetched_at;
+        self
+    }
+    /// 获取知乎回答的 html, 非成功的状态码返回 [`ZhihuError::HttpStatus`]
+    #[cfg(feature = "network")]
     pub async fn request(question: usize, answer: usize) -> ZhihuResult<String> {
-        let url = format!("https://www.zhihu.com/question/{question}/answer/{answer}");
-        let resp = reqwest::Client::new().get(url).send().await?;
-        Ok(resp.text().await?)
+        ZhihuClient::default().get_text(&answer_url(question, answer)).await
+    }
+    /// 获取知乎回答正文的 html, 不转换为 markdown, 找不到正文时返回 [`ZhihuError::MissingContent`]
+    #[cfg(feature = "network")]
+    pub async fn request_content_html(question: usize, answer: usize) -> ZhihuResult<String> {
+        let html = Self::request(question, answer).await?;
+        Self::default().clean_content_html(&html)
+    }
+    /// 回答的永久链接, 形如 `https://www.zhihu.com/question/<q>/answer/<a>`
+    pub fn permalink(&self) -> Option<&str> {
+        self.permalink.as_deref()
+    }
+    /// 估计的阅读时间, 单位为分钟
+    pub fn reading_minutes(&self) -> usize {
+        reading_minutes(&self.content)
+    }
+    /// 正文的 html, 已去掉 [`RenderOptions::remove_selectors`] 匹配的部分, 可交给其他转换器
+    pub fn content_html(&self) -> &str {
+        &self.source
+    }
+    /// 转换时未能识别, 只保留了文本的标签
+    pub fn unhandled_tags(&self) -> &[String] {
+        &self.unhandled_tags
+    }
+    /// 问题的标题
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    /// 问题的描述, 已转换为 markdown, 没有描述时为空
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    /// 回答者的名字, 匿名回答为 `匿名用户`
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+    /// 是否为匿名回答, 包括已注销的 `知乎用户`
+    pub fn is_anonymous(&self) -> bool {
+        self.author.as_deref().is_some_and(is_anonymous_name)
+    }
+    /// 作者的名字, 简介, 头像和主页链接, 匿名回答为 `None`
+    pub fn author_info(&self) -> Option<&AuthorInfo> {
+        self.author_info.as_ref()
+    }
+    /// 发布的时间, 即页面上 `发布于` 之后的文字, 或 `dateCreated` 元数据
+    pub fn published_at(&self) -> Option<&str> {
+        self.published.as_deref()
+    }
+    /// 最后编辑的时间, 即页面上 `编辑于` 之后的文字, 或 `dateModified` 元数据, 未编辑过的回答为 `None`
+    pub fn edited_at(&self) -> Option<&str> {
+        self.edited.as_deref()
+    }
+    /// 作者是否设置了禁止转载
+    pub fn is_repost_forbidden(&self) -> bool {
+        self.repost_forbidden
+    }
+    /// 谁可以评论, 页面数据中的 `commentPermission`, 如 `all`, `followee`, `censor` 或 `nobody`
+    pub fn comment_permission(&self) -> Option<&str> {
+        self.comment_permission.as_deref()
+    }
+    /// 评论区是否已关闭
+    pub fn comments_closed(&self) -> bool {
+        self.comments_closed
+    }
+    /// 回答的评论, 调用 [`Self::with_comments`] 或 [`Self::fetch_comments`] 之后才有
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+    #[cfg(feature = "network")]
+    /// 为 `true` 时使用默认的客户端获取评论及其全部回复, 见 [`Self::fetch_comments`]
+    pub async fn with_comments(mut self, with_comments: bool) -> ZhihuResult<Self> {
+        if with_comments {
+            self.fetch_comments(&ZhihuClient::default()).await?;
+        }
+        Ok(self)
+    }
+    #[cfg(feature = "network")]
+    /// 通过评论接口获取评论及其全部回复, 之后渲染的 markdown 在文末附上 `## 评论` 一节
+    pub async fn fetch_comments(&mut self, client: &ZhihuClient) -> ZhihuResult<()> {
+        let id = match self.permalink.as_deref().and_then(UrlDispatcher::classify) {
+            Some(ContentKind::Answer { answer, .. }) => answer,
+            _ => return Err(ZhihuError::MissingContent("link[rel=\"canonical\"]".to_string())),
+        };
+        self.comments = fetch_comments(client, "answers", id).await?;
+        Ok(())
+    }
+    /// 回答是否已被删除
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+    /// 收藏数, 开启 [`RenderOptions::engagement`] 时写入 frontmatter
+    pub fn favorite_count(&self) -> Option<usize> {
+        self.engagement.favorites
+    }
+    /// 分享数, 仅供统计, 不会出现在 markdown 中
+    pub fn share_count(&self) -> Option<usize> {
+        self.engagement.shares
+    }
+    /// 赞同数, 评论数, 收藏数和分享数, 优先读取按钮上的数字, 其次是页面数据
+    pub fn engagement(&self) -> Engagement {
+        self.engagement
+    }
+    /// 问题所属的话题, 开启 [`RenderOptions::frontmatter`] 时写入 `tags`
+    pub fn topics(&self) -> &[String] {
+        &self.topics
+    }
+    /// 页面推荐的相关内容, 开启 [`RenderOptions::related`] 时作为附录输出
+    pub fn related(&self) -> &[RelatedLink] {
+        &self.related
+    }
+    /// 只渲染第一个匹配选择器的部分, 匹配到标题时包括直到下一个同级标题的整节
+    pub fn render_section(&self, selector: &Selector) -> ZhihuResult<Option<String>> {
+        render_section(&self.options, &self.source, selector)
     }
     pub fn save<P>(&self, path: P) -> ZhihuResult<()>
     where
         P: AsRef<Path>,
     {
+        self.check_copyright()?;
         let mut file = std::fs::File::create(path)?;
         file.write_all(self.to_string().as_bytes())?;
         Ok(())
     }
+    #[cfg(feature = "network")]
+    /// 保存到 `dir` 目录, markdown 写入 `dir/index.md`, 图片下载到 `dir/assets` 并改写为相对链接
+    ///
+    /// 下载失败的图片保留原始链接, 返回的报告列出成功与失败的图片, 以便重试或记录
+    pub async fn save_with_assets<P>(&self, dir: P) -> ZhihuResult<AssetReport>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        self.save_with_assets_progress(&ZhihuClient::default(), dir.join("index.md"), dir.join("assets"), |_| {}).await
+    }
+    #[cfg(feature = "network")]
+    /// 同 [`Self::save_with_assets`], 但 markdown 写入 `path`, 图片下载到 `assets`
+    ///
+    /// 使用给定的客户端下载图片, 每张图片下载结束后调用 `progress` 报告进度
+    pub async fn save_with_assets_progress<P, Q, F>(
+        &self,
+        client: &ZhihuClient,
+        path: P,
+        assets: Q,
+        progress: F,
+    ) -> ZhihuResult<AssetReport>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        F: Fn(Progress) + Send + Sync,
+    {
+        self.check_copyright()?;
+        let (markdown, report) =
+            localize_images(client, self.to_string(), &self.images, path.as_ref(), assets.as_ref(), &progress).await?;
+        tokio::fs::write(path, markdown).await?;
+        Ok(report)
+    }
     fn do_parse(&mut self, html: &str) -> ZhihuResult<()> {
-        let html = Html::parse_document(html);
+        let mut html = Html::parse_document(html);
+        self.options.prune(&mut html);
         self.extract_title(&html)?;
+        self.extract_permalink(&html)?;
         self.extract_description(&html)?;
+        self.extract_author(&html)?;
+        self.extract_times(&html)?;
+        self.extract_copyright(&html)?;
+        self.extract_state(&html)?;
         self.extract_content(&html)?;
+        self.topics = topics(&html);
+        self.related = related_links(&html);
         Ok(())
     }
     fn extract_title(&mut self, html: &Html) -> ZhihuResult<()> {
         let selector = Selector::new("h1.QuestionHeader-title");
-        let _: Option<_> = try {
-            let node = html.select(&selector).next()?;
-            let text = node.first_child()?.as_text()?;
-            self.title = text.to_string();
-        };
+        let title = html.select(&selector).next().map(|node| node.inner_text().trim().to_string());
+        match title.filter(|title| !title.is_empty()).or_else(|| page_title(html)) {
+            Some(title) if !title.is_empty() => self.title = title,
+            _ => self.options.missing_content("h1.QuestionHeader-title")?,
+        }
+        Ok(())
+    }
+    fn extract_permalink(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new(r#"link[rel="canonical"], div.AnswerItem meta[itemprop="url"]"#);
+        for node in html.select(&selector) {
+            let href = if node.is_a("link") { node.get_attribute("href") } else { node.get_attribute("content") };
+            if let Some(ContentKind::Answer { question, answer }) = UrlDispatcher::classify(href) {
+                self.permalink = Some(answer_url(question, answer));
+                break;
+            }
+        }
         Ok(())
     }
     fn extract_description(&mut self, html: &Html) -> ZhihuResult<()> {
         let selector = Selector::new("div.QuestionRichText");
-        let _: Option<_> = try {
-            for node in html.select(&selector) {
-                let text = node.first_child()?.as_text()?;
-                println!("text: {:?}", text);
+        if let Some(node) = html.select(&selector).next() {
+            // the question is not part of the answer, so its unknown tags are reported rather than failing the parse
+            let options = RenderOptions { mode: ParseMode::Lenient, ..self.options.clone() };
+            let mut renderer = MarkdownRenderer::new(&options);
+            renderer.render_children(node)?;
+            self.description = renderer.content.trim().to_string();
+            self.unhandled_tags = renderer.unhandled_tags;
+            // written before the answer, so its images and references come first
+            if self.options.question_description {
+                self.images = renderer.images;
+                self.references = renderer.references;
             }
-        };
+        }
         Ok(())
     }
-    fn extract_content(&mut self, html: &Html) -> ZhihuResult<()> {
+    fn extract_author(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new(r#"div.AuthorInfo meta[itemprop="name"], .AuthorInfo-name"#);
+        self.author = html
+            .select(&selector)
+            .map(|node| if node.is_a("meta") { node.get_attribute("content").to_string() } else { node.inner_text() })
+            .map(|name| name.trim().to_string())
+            .find(|name| !name.is_empty());
+        // an anonymous author has no profile to introduce
+        self.author_info = author_info(html).filter(|info| !is_anonymous_name(&info.name));
+        Ok(())
+    }
+    fn extract_times(&mut self, html: &Html) -> ZhihuResult<()> {
+        (self.published, self.edited) = content_times(html);
+        Ok(())
+    }
+    fn extract_copyright(&mut self, html: &Html) -> ZhihuResult<()> {
+        // the notice sits next to the content of this answer, other answers of the page, scripts and an author
+        // quoting it in the answer do not count
+        let item = content_item(html, "span.CopyrightRichText-richText", self.answer_id());
+        let content: Vec<_> = item.select(&Selector::new("span.CopyrightRichText-richText")).collect();
+        self.repost_forbidden = item.descendants().any(|node| {
+            node.as_text().is_some_and(|text| text.contains("禁止转载"))
+                && !std::iter::successors(node.parent(), Node::parent)
+                    .any(|parent| content.contains(&parent) || parent.is_a("script") || parent.is_a("style"))
+        });
+        Ok(())
+    }
+    /// The id of the answer, as its permalink tells.
+    fn answer_id(&self) -> Option<usize> {
+        match self.permalink.as_deref().and_then(UrlDispatcher::classify) {
+            Some(ContentKind::Answer { answer, .. }) => Some(answer),
+            _ => None,
+        }
+    }
+    fn extract_state(&mut self, html: &Html) -> ZhihuResult<()> {
+        // a page that cannot be parsed leaves the state unknown rather than failing the answer
+        let entity = initial_entity(html, "answers", self.answer_id());
+        let item = content_item(html, "span.CopyrightRichText-richText", self.answer_id());
+        self.engagement = Engagement::extract(item, entity.as_ref());
+        if let Some(entity) = entity {
+            self.comment_permission = entity["commentPermission"].as_str().map(str::to_string);
+            self.comments_closed = self.comment_permission.as_deref() == Some("nobody")
+                || entity.pointer("/canComment/status").and_then(Value::as_bool) == Some(false);
+        }
+        Ok(())
+    }
+    /// Refuse to save under [`CopyrightPolicy::Refuse`] when the author forbids reposting.
+    fn check_copyright(&self) -> ZhihuResult<()> {
+        match self.repost_forbidden && self.options.copyright == CopyrightPolicy::Refuse {
+            true => Err(ZhihuError::RepostForbidden(self.permalink.clone().unwrap_or_else(|| self.title.clone()))),
+            false => Ok(()),
+        }
+    }
+    /// The pruned html of the content, found like [`Self::extract_content`] but never rendered.
+    #[cfg(feature = "network")]
+    fn clean_content_html(mut self, html: &str) -> ZhihuResult<String> {
+        let mut html = Html::parse_document(html);
+        self.options.prune(&mut html);
+        self.extract_permalink(&html)?;
+        match self.content_node(&html) {
+            Some(node) => Ok(node.inner_html()),
+            None => Err(ZhihuError::MissingContent("span.CopyrightRichText-richText".to_string())),
+        }
+    }
+    /// The content of this answer, within its item when the page shows several.
+    fn content_node<'a>(&self, html: &'a Html) -> Option<Node<'a>> {
         // div.RichContent-inner
         let selector = Selector::new("span.CopyrightRichText-richText");
-        let _: Option<_> = try {
-            let node = html.select(&selector).next()?;
-            for child in node.children() {
-                self.read_content_node(child).ok()?;
-            }
-        };
-        Ok(())
+        let item = content_item(html, "span.CopyrightRichText-richText", self.answer_id());
+        item.select(&selector).next()
     }
-    fn read_content_node(&mut self, node: Node) -> ZhihuResult<()> {
-        match node.as_kind() {
-            NodeKind::Document => {
-                println!("document")
-            }
-            NodeKind::Fragment => {
-                println!("fragment")
-            }
-            NodeKind::Doctype(_) => {
-                println!("doctype")
-            }
-            NodeKind::Comment(_) => {
-                println!("comment")
-            }
-            NodeKind::Text(t) => {
-                self.content.push_str(t.trim());
-            }
-            NodeKind::Element(e) => {
-                match e.name() {
-                    "p" => {
-                        for child in node.children() {
-                            self.read_content_node(child)?;
-                        }
-                        self.content.push_str("\n\n");
-                    }
-                    "span" => {
-                        // math mode
-                        if e.has_class("ztext-math") {
-                            match e.get_attribute("data-tex") {
-                                Some(s) => {
-                                    self.content.push_str(" $$");
-                                    self.content.push_str(s);
-                                    self.content.push_str("$$ ");
-                                }
-                                None => {}
-                            }
-                        }
-                        // normal mode
-                        else {
-                            for child in node.children() {
-                                self.read_content_node(child)?;
-                            }
-                        }
-                    }
-                    "br" => {
-                        self.content.push_str("\n");
-                    }
-                    "figure" => {
-                        for child in node.descendants().filter(|e| e.has_class("img")) {
-                            let original = child.get_attribute("data-original");
-                            if !original.is_empty() {
-                                write!(self.content, "![]({})", original)?;
-                                break;
-                            }
-                        }
-                    }
-                    unknown => panic!("unknown element: {unknown}"),
-                }
+    /// The item of this answer when it has no content to find it by: the one named after the answer, or else the
+    /// first answer item of the page.
+    fn deleted_item<'a>(&self, html: &'a Html) -> Option<Node<'a>> {
+        let selector = Selector::new(".AnswerItem");
+        let mut items = html.select(&selector);
+        match self.answer_id() {
+            Some(id) => items.find(|item| item.get_attribute("name") == id.to_string()),
+            None => items.next(),
+        }
+    }
+    fn extract_content(&mut self, html: &Html) -> ZhihuResult<()> {
+        match self.content_node(html) {
+            Some(node) => {
+                let mut renderer = MarkdownRenderer::new(&self.options);
+                // continue after what the description already collected
+                renderer.unhandled_tags = std::mem::take(&mut self.unhandled_tags);
+                renderer.images = std::mem::take(&mut self.images);
+                renderer.references = std::mem::take(&mut self.references);
+                renderer.render_children(node)?;
+                self.source = node.inner_html();
+                self.content = renderer.content;
+                self.unhandled_tags = renderer.unhandled_tags;
+                self.images = renderer.images;
+                self.references = renderer.references;
             }
-            NodeKind::ProcessingInstruction(_) => {
-                println!("processing instruction");
+            // a deleted answer has no content, only a notice within its own item
+            None if self.deleted_item(html).is_some_and(|item| shows_text(item, "该回答已被删除")) => {
+                self.deleted = true
             }
+            None => self.options.missing_content("span.CopyrightRichText-richText")?,
         }
         Ok(())
     }
 }
+
+/// Whether `text` is shown within `node`, text of scripts and styles does not count.
+fn shows_text(node: Node, text: &str) -> bool {
+    node.descendants().any(|child| {
+        child.as_text().is_some_and(|shown| shown.contains(text))
+            && !std::iter::successors(child.parent(), Node::parent).any(|parent| parent.is_a("script") || parent.is_a("style"))
+    })
+}
+
+fn is_anonymous_name(name: &str) -> bool {
+    matches!(name, "匿名用户" | "知乎用户")
+}
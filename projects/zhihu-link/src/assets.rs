@@ -0,0 +1,183 @@
+use crate::{Progress, ZhihuClient, ZhihuResult};
+use futures::{StreamExt, stream};
+use std::{
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// How many images are downloaded at the same time.
+const CONCURRENT_DOWNLOADS: usize = 8;
+
+/// What became of the images of a page saved with its assets, so that failed downloads can be retried or logged.
+///
+/// Both lists follow the order in which the images first appear on the page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetReport {
+    /// The urls that were downloaded, with the file name they were saved as inside the assets directory.
+    pub downloaded: Vec<(String, String)>,
+    /// The urls that keep their remote link, with the reason their download failed.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Download `images` into `assets` through `client` and point the markdown links at the local copies.
+///
+/// Identical urls are fetched once and identical images stored once, images that fail to download keep their
+/// remote url.
+/// `progress` is told about every finished download.
+pub(crate) async fn localize_images(
+    client: &ZhihuClient,
+    mut markdown: String,
+    images: &[String],
+    path: &Path,
+    assets: &Path,
+    progress: &(dyn Fn(Progress) + Send + Sync),
+) -> ZhihuResult<(String, AssetReport)> {
+    tokio::fs::create_dir_all(assets).await?;
+    let mut unique: Vec<&str> = Vec::with_capacity(images.len());
+    for url in images {
+        if !unique.contains(&url.as_str()) {
+            unique.push(url);
+        }
+    }
+    let client = client.http();
+    let total = unique.len();
+    let mut done = 0;
+    let mut downloaded: Vec<(usize, &str, Result<String, String>)> = stream::iter(unique.into_iter().enumerate())
+        .map(|(index, url)| async move { (index, url, download(client, url, assets).await) })
+        .buffer_unordered(CONCURRENT_DOWNLOADS)
+        .inspect(|_| {
+            done += 1;
+            progress(Progress { done, total });
+        })
+        .collect()
+        .await;
+    downloaded.sort_by_key(|(index, ..)| *index);
+    let relative = relative_dir(path, assets);
+    let mut report = AssetReport::default();
+    for (_, url, file) in downloaded {
+        match file {
+            Ok(file) => {
+                markdown = replace_image_url(&markdown, url, &format!("{relative}{file}"));
+                report.downloaded.push((url.to_string(), file));
+            }
+            Err(reason) => report.failed.push((url.to_string(), reason)),
+        }
+    }
+    Ok((markdown, report))
+}
+
+/// Point the images at `url` to `local`, links to the same url keep pointing at it.
+fn replace_image_url(markdown: &str, url: &str, local: &str) -> String {
+    let pattern = format!("]({url}");
+    let mut out = String::with_capacity(markdown.len());
+    let mut rest = 0;
+    for (at, _) in markdown.match_indices(&pattern) {
+        let end = at + pattern.len();
+        let closed = markdown[end..].starts_with(')') || markdown[end..].starts_with(" \"");
+        if !closed || !opens_image(&markdown[..at]) {
+            continue;
+        }
+        out.push_str(&markdown[rest..at + 2]);
+        out.push_str(local);
+        rest = end;
+    }
+    out.push_str(&markdown[rest..]);
+    out
+}
+
+/// Whether the bracketed text ending at the end of `before` opens with `![`, as an image does.
+fn opens_image(before: &str) -> bool {
+    let bytes = before.as_bytes();
+    let mut depth = 0;
+    for i in (0..bytes.len()).rev() {
+        let escaped = i > 0 && bytes[i - 1] == b'\\';
+        match bytes[i] {
+            b']' if !escaped => depth += 1,
+            b'[' if !escaped && depth == 0 => return i > 0 && bytes[i - 1] == b'!',
+            b'[' if !escaped => depth -= 1,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Save one image into `assets`, the error is the reason in words.
+async fn download(client: &reqwest::Client, url: &str, assets: &Path) -> Result<String, String> {
+    let request = match url.starts_with("//") {
+        true => client.get(format!("https:{url}")),
+        false => client.get(url),
+    };
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("http status {}", status));
+    }
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    store(assets, url, &bytes).await.map_err(|e| e.to_string())
+}
+
+/// Write `content` into `assets` under the name of its hash, returning that name.
+///
+/// A file with the same content, such as the same picture served by another mirror of the cdn, is reused.
+/// One left half written by an older run no longer matches its hash and is replaced, while a different image
+/// whose hash collides gets a numbered name of its own.
+async fn store(assets: &Path, url: &str, content: &[u8]) -> std::io::Result<String> {
+    let name = asset_name(url, content);
+    let mut candidate = name.clone();
+    let mut number = 0;
+    loop {
+        let file = assets.join(&candidate);
+        match tokio::fs::read(&file).await {
+            Ok(existing) if existing == content => return Ok(candidate),
+            Ok(existing) if asset_name(url, &existing) == name => {
+                number += 1;
+                candidate = match name.split_once('.') {
+                    Some((stem, ext)) => format!("{stem}-{number}.{ext}"),
+                    None => format!("{name}-{number}"),
+                };
+            }
+            _ => {
+                write_atomically(&file, content).await?;
+                return Ok(candidate);
+            }
+        }
+    }
+}
+
+/// Write through a temporary file renamed into place, so an interrupted run never leaves a partial `file`.
+async fn write_atomically(file: &Path, content: &[u8]) -> std::io::Result<()> {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let write = WRITES.fetch_add(1, Ordering::Relaxed);
+    let part = file.with_file_name(format!(".{}.{}-{}.part", name, std::process::id(), write));
+    tokio::fs::write(&part, content).await?;
+    if let Err(error) = tokio::fs::rename(&part, file).await {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// A stable file name: the FNV-1a hash of the content plus the extension of the url.
+fn asset_name(url: &str, content: &[u8]) -> String {
+    let hash = content.iter().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3));
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let file = path.rsplit('/').next().unwrap_or(path);
+    match file.rsplit_once('.') {
+        Some((_, ext)) if !ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            format!("{hash:016x}.{ext}")
+        }
+        _ => format!("{hash:016x}"),
+    }
+}
+
+/// The assets directory as seen from the markdown file, ending with `/` unless empty.
+fn relative_dir(path: &Path, assets: &Path) -> String {
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    let relative = assets.strip_prefix(base).unwrap_or(assets);
+    let mut out: String = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+    if !out.is_empty() {
+        out.push('/');
+    }
+    out
+}
@@ -0,0 +1,119 @@
+use crate::{ZhihuAnswer, ZhihuArticle, ZhihuPin};
+#[cfg(feature = "network")]
+use crate::{ZhihuClient, ZhihuError, ZhihuResult};
+use std::fmt::{Display, Formatter};
+use url::Url;
+
+/// The kind of content a url points to, with the ids needed to fetch it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ContentKind {
+    /// `https://www.zhihu.com/question/<question>/answer/<answer>`
+    Answer {
+        /// The question id.
+        question: usize,
+        /// The answer id.
+        answer: usize,
+    },
+    /// `https://www.zhihu.com/question/<id>`
+    Question {
+        /// The question id.
+        id: usize,
+    },
+    /// `https://www.zhihu.com/pin/<id>`
+    Pin {
+        /// The pin id.
+        id: usize,
+    },
+    /// `https://zhuanlan.zhihu.com/p/<id>`
+    Article {
+        /// The article id.
+        id: usize,
+    },
+    /// `https://www.bilibili.com/read/cv<id>`
+    BilibiliArticle {
+        /// The number after `cv`.
+        id: usize,
+    },
+}
+
+/// Recognizes which kind of content a url points to.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct UrlDispatcher;
+
+impl UrlDispatcher {
+    /// Classify a url, returns `None` for urls that are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zhihu_link::{ContentKind, UrlDispatcher};
+    /// let kind = UrlDispatcher::classify("https://zhuanlan.zhihu.com/p/620388361");
+    /// assert_eq!(kind, Some(ContentKind::Article { id: 620388361 }));
+    /// ```
+    pub fn classify(url: &str) -> Option<ContentKind> {
+        let url = Url::parse(url).ok()?;
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        match (url.host_str()?, segments.as_slice()) {
+            ("www.zhihu.com" | "zhihu.com", ["question", question, "answer", answer, ..]) => {
+                Some(ContentKind::Answer { question: question.parse().ok()?, answer: answer.parse().ok()? })
+            }
+            ("www.zhihu.com" | "zhihu.com", ["question", id]) => Some(ContentKind::Question { id: id.parse().ok()? }),
+            ("www.zhihu.com" | "zhihu.com", ["pin", id]) => Some(ContentKind::Pin { id: id.parse().ok()? }),
+            ("zhuanlan.zhihu.com", ["p", id]) => Some(ContentKind::Article { id: id.parse().ok()? }),
+            ("www.bilibili.com" | "bilibili.com", ["read", cv]) => {
+                Some(ContentKind::BilibiliArticle { id: cv.strip_prefix("cv")?.parse().ok()? })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Content fetched from a url, dispatched to the matching type.
+#[derive(Debug, Clone)]
+pub enum ZhihuAuto {
+    /// A zhihu answer.
+    Answer(ZhihuAnswer),
+    /// A zhihu column article.
+    Article(ZhihuArticle),
+    /// A zhihu pin.
+    Pin(ZhihuPin),
+}
+
+impl Display for ZhihuAuto {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZhihuAuto::Answer(answer) => Display::fmt(answer, f),
+            ZhihuAuto::Article(article) => Display::fmt(article, f),
+            ZhihuAuto::Pin(pin) => Display::fmt(pin, f),
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+impl ZhihuAuto {
+    /// Fetch and render whatever the url points to.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use zhihu_link::ZhihuAuto;
+    /// # async fn run() -> zhihu_link::ZhihuResult<()> {
+    /// let article = ZhihuAuto::new("https://zhuanlan.zhihu.com/p/620388361").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new(url: &str) -> ZhihuResult<Self> {
+        Self::with_client(url, &ZhihuClient::default()).await
+    }
+    /// Fetch and render whatever the url points to, sending the requests through `client`.
+    pub async fn with_client(url: &str, client: &ZhihuClient) -> ZhihuResult<Self> {
+        match UrlDispatcher::classify(url) {
+            Some(ContentKind::Answer { question, answer }) => {
+                Ok(ZhihuAuto::Answer(ZhihuAnswer::with_client(client, question, answer).await?))
+            }
+            Some(ContentKind::Article { id }) => Ok(ZhihuAuto::Article(ZhihuArticle::with_client(client, id).await?)),
+            Some(ContentKind::Pin { id }) => Ok(ZhihuAuto::Pin(ZhihuPin::with_client(client, id).await?)),
+            _ => Err(ZhihuError::UnsupportedUrl(url.to_string())),
+        }
+    }
+}
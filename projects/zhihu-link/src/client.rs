@@ -0,0 +1,91 @@
+use crate::{RenderOptions, ZhihuError, ZhihuResult, utils::decode_html};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The http client used to fetch pages, build it from a [`reqwest::Client`] to control headers, proxies or cookies.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::ZhihuClient;
+/// let inner = reqwest::Client::builder().user_agent("zhihu-link").build().unwrap();
+/// let client = ZhihuClient::from(inner);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZhihuClient {
+    inner: reqwest::Client,
+    api_base: String,
+}
+
+/// Where the json api of zhihu lives.
+const API_BASE: &str = "https://www.zhihu.com/api/v4";
+
+impl Default for ZhihuClient {
+    fn default() -> Self {
+        Self::from(reqwest::Client::default())
+    }
+}
+
+impl From<reqwest::Client> for ZhihuClient {
+    fn from(inner: reqwest::Client) -> Self {
+        Self { inner, api_base: API_BASE.to_string() }
+    }
+}
+
+impl ZhihuClient {
+    /// Send api requests to `base` instead of `https://www.zhihu.com/api/v4`, for example a caching mirror.
+    pub fn api_base(mut self, base: &str) -> Self {
+        self.api_base = base.trim_end_matches('/').to_string();
+        self
+    }
+    /// The underlying client, for requests that are not pages such as image downloads.
+    pub(crate) fn http(&self) -> &reqwest::Client {
+        &self.inner
+    }
+    /// The url of an api endpoint, `path` starts with `/`.
+    pub(crate) fn api_url(&self, path: &str) -> String {
+        format!("{}{}", self.api_base, path)
+    }
+    /// Fetch the page behind `url` as text, a status other than success is an error instead of content.
+    pub(crate) async fn get_text(&self, url: &str) -> ZhihuResult<String> {
+        let resp = self.inner.get(url).send().await?;
+        if !resp.status().is_success() {
+            return Err(ZhihuError::HttpStatus { status: resp.status().as_u16(), url: url.to_string() });
+        }
+        let content_type = match resp.headers().get(reqwest::header::CONTENT_TYPE) {
+            Some(value) => value.to_str().ok().map(str::to_string),
+            None => None,
+        };
+        let bytes = resp.bytes().await?;
+        decode_html(&bytes, content_type.as_deref())
+    }
+}
+
+/// The current time in seconds since the unix epoch, read only when `options` records provenance.
+pub(crate) fn fetch_time(options: &RenderOptions) -> Option<u64> {
+    options.provenance.then(unix_time)
+}
+
+/// The current time in seconds since the unix epoch.
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+/// Every item of the api list at `url`, fetched `page_size` at a time by `offset` until the last page.
+pub(crate) async fn paginate(client: &ZhihuClient, url: &str, page_size: usize) -> ZhihuResult<Vec<Value>> {
+    let mut items = vec![];
+    loop {
+        let mut page = url::Url::parse(url)?;
+        page.query_pairs_mut().append_pair("offset", &items.len().to_string()).append_pair("limit", &page_size.to_string());
+        let mut json: Value = serde_json::from_str(&client.get_text(page.as_str()).await?)?;
+        let is_end = json["paging"]["is_end"].as_bool().unwrap_or(true);
+        match json["data"].take() {
+            Value::Array(data) if !data.is_empty() => items.extend(data),
+            _ => break,
+        }
+        if is_end {
+            break;
+        }
+    }
+    Ok(items)
+}
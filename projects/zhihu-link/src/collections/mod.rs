@@ -0,0 +1,105 @@
+use crate::{
+    ContentKind, RenderOptions, ZhihuClient, ZhihuResult,
+    client::paginate,
+    exporter::{Exporter, content_id},
+    utils::json_id,
+};
+use serde_json::Value;
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// How many items of a collection are listed per request.
+const PAGE_SIZE: usize = 20;
+
+/// A zhihu collection (收藏夹), archived as one markdown file per saved answer or article.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use zhihu_link::ZhihuCollection;
+/// # async fn run() -> zhihu_link::ZhihuResult<()> {
+/// let reports = ZhihuCollection::new(19888888).concurrency(2).export("collection").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZhihuCollection {
+    id: usize,
+    export: Exporter,
+}
+
+impl ZhihuCollection {
+    /// 通过收藏夹 ID 创建, 使用默认的客户端
+    pub fn new(id: usize) -> Self {
+        Self::with_client(ZhihuClient::default(), id)
+    }
+    /// 通过收藏夹 ID 创建, 使用给定的客户端
+    pub fn with_client(client: ZhihuClient, id: usize) -> Self {
+        Self { id, export: Exporter::new(client) }
+    }
+    /// 渲染回答和文章的选项
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.export.options = options;
+        self
+    }
+    /// 同时获取的条目数, 至少为 1, 默认为 4
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.export.concurrency = concurrency.max(1);
+        self
+    }
+    /// 相邻两次获取条目之间的最短间隔, 用于避免被限流, 默认不等待
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.export.interval = interval;
+        self
+    }
+    /// 收藏夹 ID
+    pub fn id(&self) -> usize {
+        self.id
+    }
+    /// 逐页列出收藏的回答和文章, 按收藏夹中的顺序, 其他类型的条目被跳过
+    pub async fn items(&self) -> ZhihuResult<Vec<ContentKind>> {
+        let client = &self.export.client;
+        let items = paginate(client, &client.api_url(&format!("/collections/{}/items", self.id)), PAGE_SIZE).await?;
+        Ok(items.iter().filter_map(|item| collection_item(&item["content"])).collect())
+    }
+    /// 获取收藏夹的全部条目, 每个条目保存为 `dir` 下的一个 markdown 文件, 并写入链接到它们的 `index.md`
+    ///
+    /// 返回每个条目写入的文件或失败的原因, 按收藏夹中的顺序, 只有列出条目或写入 `dir` 失败时中止
+    pub async fn export<P>(&self, dir: P) -> ZhihuResult<Vec<ZhihuResult<PathBuf>>>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let items = self.items().await?;
+        let width = items.len().to_string().len().max(3);
+        let reports = self
+            .export
+            .save_all(&items, dir, |index, item| match content_id(item) {
+                Some(id) => format!("{:0width$}-{}.md", index + 1, id),
+                None => format!("{:0width$}.md", index + 1),
+            })
+            .await;
+        let mut index = format!("# 收藏夹 {}\n\n", self.id);
+        for (title, path) in reports.iter().flatten() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            writeln!(index, "- [{}]({})", title, name)?;
+        }
+        std::fs::write(dir.join("index.md"), index)?;
+        Ok(reports.into_iter().map(|report| report.map(|(_, path)| path)).collect())
+    }
+}
+
+/// An answer or an article saved in a collection.
+fn collection_item(content: &Value) -> Option<ContentKind> {
+    match content["type"].as_str()? {
+        "answer" => {
+            Some(ContentKind::Answer { question: json_id(&content["question"]["id"])?, answer: json_id(&content["id"])? })
+        }
+        "article" => Some(ContentKind::Article { id: json_id(&content["id"])? }),
+        _ => None,
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(feature = "network")]
+use crate::{ZhihuClient, ZhihuResult, utils::json_id};
+#[cfg(feature = "network")]
+use htmler::Html;
+#[cfg(feature = "network")]
+use serde_json::Value;
+#[cfg(feature = "network")]
+use std::collections::HashSet;
+
+/// A comment under an answer or an article, with the replies to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The name of the commenter.
+    pub author: String,
+    /// The text of the comment, without markup.
+    pub content: String,
+    /// The replies, oldest first.
+    pub replies: Vec<Comment>,
+}
+
+impl Comment {
+    /// Read a comment of the comments api, with the replies it embeds.
+    #[cfg(feature = "network")]
+    fn from_json(json: &Value) -> Self {
+        let author = json["author"]["member"]["name"].as_str().or_else(|| json["author"]["name"].as_str());
+        let replies = json["child_comments"].as_array().into_iter().flatten().map(Self::from_json).collect();
+        Self {
+            author: author.unwrap_or("匿名用户").trim().to_string(),
+            content: comment_text(json["content"].as_str().unwrap_or_default()),
+            replies,
+        }
+    }
+}
+
+/// Fetch every root comment of `/<kind>/<id>`, such as `answers`, with all of their replies.
+///
+/// A root comment only embeds its first replies, the rest are fetched when it counts more.
+#[cfg(feature = "network")]
+pub(crate) async fn fetch_comments(client: &ZhihuClient, kind: &str, id: usize) -> ZhihuResult<Vec<Comment>> {
+    let url = client.api_url(&format!("/{kind}/{id}/root_comments?order=normal&limit=20&offset=0"));
+    let mut comments = vec![];
+    for json in fetch_pages(client, url).await? {
+        let mut comment = Comment::from_json(&json);
+        let count = json["child_comment_count"].as_u64().unwrap_or(0) as usize;
+        if count > comment.replies.len() {
+            if let Some(id) = json_id(&json["id"]) {
+                let url = client.api_url(&format!("/comments/{id}/child_comments?limit=20&offset=0"));
+                comment.replies = fetch_pages(client, url).await?.iter().map(Comment::from_json).collect();
+            }
+        }
+        comments.push(comment);
+    }
+    Ok(comments)
+}
+
+/// Every item of a paginated list of the comments api, following the `next` links from `url`.
+///
+/// Stops at an empty page or at a `next` link that was already followed, which would loop forever.
+#[cfg(feature = "network")]
+async fn fetch_pages(client: &ZhihuClient, mut url: String) -> ZhihuResult<Vec<Value>> {
+    let mut items = vec![];
+    let mut followed = HashSet::new();
+    while followed.insert(url.clone()) {
+        let mut json: Value = serde_json::from_str(&client.get_text(&url).await?)?;
+        let data = match json["data"].take() {
+            Value::Array(data) if !data.is_empty() => data,
+            _ => break,
+        };
+        items.extend(data);
+        match json["paging"]["next"].as_str() {
+            Some(next) if json["paging"]["is_end"].as_bool() == Some(false) => url = next.to_string(),
+            _ => break,
+        }
+    }
+    Ok(items)
+}
+
+/// The text of the html of a comment, with line breaks kept.
+#[cfg(feature = "network")]
+fn comment_text(html: &str) -> String {
+    let html = Html::parse_fragment(&html.replace("<br>", "\n"));
+    html.root_node().inner_text().trim().to_string()
+}
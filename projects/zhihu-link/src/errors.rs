@@ -1,16 +1,51 @@
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ZhihuError {
     UnknownError,
+    /// An element the converter does not understand, only raised in [`ParseMode::Strict`](crate::ParseMode::Strict).
+    UnknownElement(String),
+    /// The selector did not match anything, only raised in [`ParseMode::Strict`](crate::ParseMode::Strict).
+    MissingContent(String),
+    /// The url does not point to content that can be converted.
+    UnsupportedUrl(String),
+    /// The author forbids reposting, raised when saving under [`CopyrightPolicy::Refuse`](crate::CopyrightPolicy::Refuse).
+    RepostForbidden(String),
+    /// The server answered with a status other than success, such as 403 for a rate limited client.
+    HttpStatus {
+        /// The status code, such as `404`.
+        status: u16,
+        /// The url that was requested.
+        url: String,
+    },
+    /// The page is not valid in the charset it declares.
+    Encoding {
+        /// The declared charset, such as `GBK`.
+        charset: String,
+        /// Where the first malformed byte sequence starts.
+        offset: usize,
+    },
 }
 
 pub type ZhihuResult<T> = Result<T, ZhihuError>;
 
+#[cfg(feature = "network")]
 impl From<reqwest::Error> for ZhihuError {
     fn from(_: reqwest::Error) -> Self {
         ZhihuError::UnknownError
     }
 }
 
+impl From<serde_json::Error> for ZhihuError {
+    fn from(_: serde_json::Error) -> Self {
+        ZhihuError::UnknownError
+    }
+}
+
+impl From<url::ParseError> for ZhihuError {
+    fn from(_: url::ParseError) -> Self {
+        ZhihuError::UnknownError
+    }
+}
+
 impl From<std::io::Error> for ZhihuError {
     fn from(_: std::io::Error) -> Self {
         ZhihuError::UnknownError
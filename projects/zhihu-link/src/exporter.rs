@@ -0,0 +1,104 @@
+use crate::{
+    ContentKind, RenderOptions, ZhihuAnswer, ZhihuArticle, ZhihuClient, ZhihuError, ZhihuResult,
+    client::fetch_time,
+    utils::{answer_url, article_url},
+};
+use futures::{StreamExt, stream};
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Fetches answers and articles, renders them and saves one markdown file each, for the collection and user exports.
+#[derive(Debug, Clone)]
+pub(crate) struct Exporter {
+    pub(crate) client: ZhihuClient,
+    pub(crate) options: RenderOptions,
+    /// How many items are fetched at the same time.
+    pub(crate) concurrency: usize,
+    /// The least time between the start of two fetches.
+    pub(crate) interval: Duration,
+}
+
+impl Exporter {
+    pub(crate) fn new(client: ZhihuClient) -> Self {
+        Self { client, options: RenderOptions::default(), concurrency: 4, interval: Duration::ZERO }
+    }
+    /// Save every item into `dir` under the name `name` gives its position and kind.
+    ///
+    /// Returns the title and file of every item or why it failed, in the order of `items`.
+    pub(crate) async fn save_all<F>(&self, items: &[ContentKind], dir: &Path, name: F) -> Vec<ZhihuResult<(String, PathBuf)>>
+    where
+        F: Fn(usize, &ContentKind) -> String,
+    {
+        let throttle = Throttle::new(self.interval);
+        let throttle = &throttle;
+        stream::iter(items.iter().enumerate())
+            .map(|(index, item)| {
+                let path = dir.join(name(index, item));
+                async move {
+                    throttle.wait().await;
+                    self.save(item, &path).await.map(|title| (title, path))
+                }
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+    /// Save one answer or article, returning its title, any other kind is an error.
+    async fn save(&self, item: &ContentKind, path: &Path) -> ZhihuResult<String> {
+        match *item {
+            ContentKind::Answer { question, answer } => {
+                let fetched_at = fetch_time(&self.options);
+                let html = self.client.get_text(&answer_url(question, answer)).await?;
+                let answer = ZhihuAnswer::from_html(&html, self.options.clone())?.with_fetched_at(fetched_at);
+                answer.save(path)?;
+                Ok(answer.title().to_string())
+            }
+            ContentKind::Article { id } => {
+                let fetched_at = fetch_time(&self.options);
+                let html = self.client.get_text(&article_url(id)).await?;
+                let article = ZhihuArticle::from_html(&html, self.options.clone())?.with_fetched_at(fetched_at);
+                article.save(path)?;
+                Ok(article.title().to_string())
+            }
+            ref other => Err(ZhihuError::UnsupportedUrl(format!("{other:?}"))),
+        }
+    }
+}
+
+/// The id an answer or article is saved under.
+pub(crate) fn content_id(item: &ContentKind) -> Option<usize> {
+    match *item {
+        ContentKind::Answer { answer, .. } => Some(answer),
+        ContentKind::Article { id } => Some(id),
+        _ => None,
+    }
+}
+
+/// Spaces the start of fetches `interval` apart, however many of them run at the same time.
+struct Throttle {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl Throttle {
+    fn new(interval: Duration) -> Self {
+        Self { interval, next: Mutex::new(Instant::now()) }
+    }
+    /// Wait for the turn of one more fetch.
+    async fn wait(&self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let start = {
+            let mut next = self.next.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let start = (*next).max(now);
+            *next = start + self.interval;
+            start
+        };
+        tokio::time::sleep(start - now).await;
+    }
+}
@@ -1,8 +1,51 @@
-#![feature(try_blocks)]
-
 mod answers;
+#[cfg(feature = "network")]
+mod assets;
+mod auto;
+#[cfg(feature = "network")]
+mod client;
+#[cfg(feature = "network")]
+mod collections;
+mod comments;
 mod errors;
+#[cfg(feature = "network")]
+mod exporter;
+mod pins;
+mod progress;
+mod questions;
+mod renders;
+#[cfg(feature = "network")]
+mod users;
+pub mod utils;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod zhuanlans;
 
 pub use errors::{ZhihuError, ZhihuResult};
+pub use htmler::Selector;
+pub use regex::Regex;
 
-pub use crate::answers::ZhihuAnswer;
+#[cfg(feature = "wasm")]
+pub use crate::wasm::render;
+pub use crate::{
+    answers::ZhihuAnswer,
+    auto::{ContentKind, UrlDispatcher, ZhihuAuto},
+    comments::Comment,
+    pins::ZhihuPin,
+    progress::Progress,
+    questions::AnswerPage,
+    renders::{
+        AuthorInfo, CopyrightPolicy, Engagement, FrontmatterFormat, MathDelimiters, ParseMode, RelatedLink, RenderOptions,
+        Typography,
+    },
+    zhuanlans::{Column, ZhihuArticle},
+};
+#[cfg(feature = "network")]
+pub use crate::{
+    assets::AssetReport,
+    client::ZhihuClient,
+    collections::ZhihuCollection,
+    questions::ZhihuQuestion,
+    users::ZhihuUser,
+    zhuanlans::{ColumnExport, ZhihuColumn},
+};
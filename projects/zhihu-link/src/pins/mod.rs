@@ -0,0 +1,230 @@
+use crate::{
+    ContentKind, RenderOptions, UrlDispatcher, ZhihuError, ZhihuResult,
+    renders::{Document, Engagement, MarkdownRenderer, page_title, write_document},
+    utils::pin_url,
+};
+#[cfg(feature = "network")]
+use crate::{
+    Progress, ZhihuClient,
+    assets::{AssetReport, localize_images},
+};
+use htmler::{Html, Node, Selector};
+use std::{
+    fmt::{Display, Formatter},
+    io::Write as _,
+    path::Path,
+    str::FromStr,
+};
+
+/// A zhihu pin (想法), a short text followed by a gallery of images.
+#[derive(Debug, Clone)]
+pub struct ZhihuPin {
+    title: String,
+    content: String,
+    permalink: Option<String>,
+    author: Option<String>,
+    unhandled_tags: Vec<String>,
+    images: Vec<String>,
+    /// When the page was fetched, in seconds since the unix epoch, unknown for html parsed from elsewhere.
+    fetched_at: Option<u64>,
+    options: RenderOptions,
+}
+
+impl Default for ZhihuPin {
+    fn default() -> Self {
+        Self {
+            title: "".to_string(),
+            content: "".to_string(),
+            permalink: None,
+            author: None,
+            unhandled_tags: vec![],
+            images: vec![],
+            fetched_at: None,
+            options: RenderOptions::default(),
+        }
+    }
+}
+
+impl Display for ZhihuPin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let byline = self.author.as_ref().map(|author| format!("作者: {}", author));
+        let document = Document {
+            title: &self.title,
+            intro: None,
+            byline: byline.as_deref(),
+            author: self.author.as_deref(),
+            topics: &[],
+            content: &self.content,
+            permalink: self.permalink.as_deref(),
+            related: &[],
+            references: &[],
+            column: None,
+            engagement: Engagement::default(),
+            published: None,
+            edited: None,
+            fetched_at: self.fetched_at,
+            repost_forbidden: false,
+            comments: &[],
+        };
+        write_document(f, &self.options, &document)
+    }
+}
+
+impl FromStr for ZhihuPin {
+    type Err = ZhihuError;
+
+    fn from_str(html: &str) -> Result<Self, Self::Err> {
+        Self::from_html(html, RenderOptions::default())
+    }
+}
+
+impl ZhihuPin {
+    #[cfg(feature = "network")]
+    /// 通过想法 ID 获取知乎想法, 并渲染为 markdown
+    pub async fn new(id: usize) -> ZhihuResult<Self> {
+        Self::with_client(&ZhihuClient::default(), id).await
+    }
+    #[cfg(feature = "network")]
+    /// 使用给定的客户端获取知乎想法, 以便控制请求头, 代理或 cookie
+    pub async fn with_client(client: &ZhihuClient, id: usize) -> ZhihuResult<Self> {
+        let html = client.get_text(&pin_url(id)).await?;
+        let mut out: Self = html.parse()?;
+        out.permalink.get_or_insert_with(|| pin_url(id));
+        Ok(out)
+    }
+    /// 解析知乎想法的 html, 并按照给定的选项渲染为 markdown
+    pub fn from_html(html: &str, options: RenderOptions) -> ZhihuResult<Self> {
+        let mut empty = Self { options, ..Self::default() };
+        empty.do_parse(html)?;
+        Ok(empty)
+    }
+    /// 设置抓取页面的时间, 以 unix 秒计, 开启 [`RenderOptions::provenance`] 时写入来源注释
+    pub fn with_fetched_at(mut self, fetched_at: Option<u64>) -> Self {
+        self.fetched_at = fetched_at;
+        self
+    }
+    /// 想法的永久链接, 形如 `https://www.zhihu.com/pin/<id>`
+    pub fn permalink(&self) -> Option<&str> {
+        self.permalink.as_deref()
+    }
+    /// 想法的作者
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+    /// 图片的链接, 按出现的顺序
+    pub fn images(&self) -> &[String] {
+        &self.images
+    }
+    /// 转换时未能识别, 只保留了文本的标签
+    pub fn unhandled_tags(&self) -> &[String] {
+        &self.unhandled_tags
+    }
+    pub fn save<P>(&self, path: P) -> ZhihuResult<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(self.to_string().as_bytes())?;
+        Ok(())
+    }
+    #[cfg(feature = "network")]
+    /// 保存到 `dir` 目录, markdown 写入 `dir/index.md`, 图片下载到 `dir/assets` 并改写为相对链接
+    ///
+    /// 下载失败的图片保留原始链接, 返回的报告列出成功与失败的图片, 以便重试或记录
+    pub async fn save_with_assets<P>(&self, dir: P) -> ZhihuResult<AssetReport>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        self.save_with_assets_progress(&ZhihuClient::default(), dir.join("index.md"), dir.join("assets"), |_| {}).await
+    }
+    #[cfg(feature = "network")]
+    /// 同 [`Self::save_with_assets`], 但 markdown 写入 `path`, 图片下载到 `assets`
+    ///
+    /// 使用给定的客户端下载图片, 每张图片下载结束后调用 `progress` 报告进度
+    pub async fn save_with_assets_progress<P, Q, F>(
+        &self,
+        client: &ZhihuClient,
+        path: P,
+        assets: Q,
+        progress: F,
+    ) -> ZhihuResult<AssetReport>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        F: Fn(Progress) + Send + Sync,
+    {
+        let (markdown, report) =
+            localize_images(client, self.to_string(), &self.images, path.as_ref(), assets.as_ref(), &progress).await?;
+        tokio::fs::write(path, markdown).await?;
+        Ok(report)
+    }
+    fn do_parse(&mut self, html: &str) -> ZhihuResult<()> {
+        let mut html = Html::parse_document(html);
+        self.options.prune(&mut html);
+        self.extract_permalink(&html)?;
+        self.extract_author(&html)?;
+        self.extract_content(&html)?;
+        // pins have no title of their own
+        self.title = match (page_title(&html), &self.author) {
+            (Some(title), _) if !title.is_empty() => title,
+            (_, Some(author)) => format!("{} 的想法", author),
+            _ => "想法".to_string(),
+        };
+        Ok(())
+    }
+    fn extract_permalink(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new(r#"link[rel="canonical"], meta[itemprop="url"]"#);
+        for node in html.select(&selector) {
+            let href = if node.is_a("meta") { node.get_attribute("content") } else { node.get_attribute("href") };
+            if let Some(ContentKind::Pin { id }) = UrlDispatcher::classify(href) {
+                self.permalink = Some(pin_url(id));
+                break;
+            }
+        }
+        Ok(())
+    }
+    fn extract_author(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new(r#"div.AuthorInfo meta[itemprop="name"], .AuthorInfo-name"#);
+        self.author = html
+            .select(&selector)
+            .map(|node| if node.is_a("meta") { node.get_attribute("content").to_string() } else { node.inner_text() })
+            .map(|name| name.trim().to_string())
+            .find(|name| !name.is_empty());
+        Ok(())
+    }
+    /// The text of the pin, then its gallery, one image per block.
+    fn extract_content(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new("div.PinItem-content");
+        let Some(root) = html.select(&selector).next()
+        else {
+            return self.options.missing_content("div.PinItem-content");
+        };
+        let text = root.select(&Selector::new(".RichText")).next();
+        let mut renderer = MarkdownRenderer::new(&self.options);
+        if let Some(text) = text {
+            renderer.render_children(text)?;
+        }
+        for img in root.descendants().filter(|n| n.is_a("img")) {
+            if !text.is_some_and(|text| is_inside(img, text)) {
+                renderer.write_picture(img)?;
+            }
+        }
+        self.content = renderer.content;
+        self.unhandled_tags = renderer.unhandled_tags;
+        self.images = renderer.images;
+        Ok(())
+    }
+}
+
+/// Whether `ancestor` encloses `node`.
+fn is_inside(node: Node, ancestor: Node) -> bool {
+    let mut parent = node.parent();
+    while let Some(node) = parent {
+        if node == ancestor {
+            return true;
+        }
+        parent = node.parent();
+    }
+    false
+}
@@ -0,0 +1,17 @@
+#[cfg(feature = "network")]
+use std::sync::Arc;
+
+/// How far a batch has got, reported once for every fetched article or downloaded image.
+///
+/// Items are counted when they finish, successfully or not, so `done` reaches `total` at the end.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// How many items are finished.
+    pub done: usize,
+    /// How many items the batch has.
+    pub total: usize,
+}
+
+/// A user supplied observer of a [`Progress`].
+#[cfg(feature = "network")]
+pub(crate) type ProgressHook = Arc<dyn Fn(Progress) + Send + Sync>;
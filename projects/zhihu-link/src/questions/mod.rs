@@ -0,0 +1,246 @@
+#[cfg(feature = "network")]
+use crate::{
+    ContentKind, RenderOptions, UrlDispatcher, ZhihuClient, ZhihuError,
+    renders::{DiscussionAnswer, write_discussion},
+    utils::{answer_url, question_url, sanitize_file_name},
+};
+use crate::{ZhihuResult, utils::json_id};
+use serde_json::Value;
+#[cfg(feature = "network")]
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// The most answers zhihu returns per page, also the default page size.
+#[cfg(feature = "network")]
+const MAX_PAGE_SIZE: usize = 20;
+
+/// The fields of an answer the feed only includes on request, needed to render the answer without fetching its page.
+#[cfg(feature = "network")]
+const ANSWER_FIELDS: &str = "data[*].content,voteup_count";
+
+/// A zhihu question, whose answers are listed page by page.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use zhihu_link::ZhihuQuestion;
+/// # async fn run() -> zhihu_link::ZhihuResult<()> {
+/// let question = ZhihuQuestion::new(58151047);
+/// let mut cursor = None;
+/// loop {
+///     let page = question.answers_from_cursor(cursor.as_deref()).await?;
+///     println!("{:?}", page.answers());
+///     match page.next_cursor() {
+///         // save the cursor to resume the crawl later
+///         Some(next) => cursor = Some(next.to_string()),
+///         None => break,
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "network")]
+#[derive(Debug, Clone)]
+pub struct ZhihuQuestion {
+    id: usize,
+    client: ZhihuClient,
+    page_size: usize,
+    options: RenderOptions,
+    min_votes: usize,
+    sort_by_votes: bool,
+}
+
+/// One page of the answers of a question.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerPage {
+    answers: Vec<usize>,
+    next_cursor: Option<String>,
+}
+
+#[cfg(feature = "network")]
+impl ZhihuQuestion {
+    /// 通过问题 ID 创建, 使用默认的客户端
+    pub fn new(id: usize) -> Self {
+        Self::with_client(ZhihuClient::default(), id)
+    }
+    /// 通过问题 ID 创建, 使用给定的客户端
+    pub fn with_client(client: ZhihuClient, id: usize) -> Self {
+        Self { id, client, page_size: MAX_PAGE_SIZE, options: RenderOptions::default(), min_votes: 0, sort_by_votes: false }
+    }
+    /// 通过问题或其下回答的链接创建, 使用给定的客户端
+    pub fn from_url(client: ZhihuClient, url: &str) -> ZhihuResult<Self> {
+        match UrlDispatcher::classify(url) {
+            Some(ContentKind::Question { id }) | Some(ContentKind::Answer { question: id, .. }) => {
+                Ok(Self::with_client(client, id))
+            }
+            _ => Err(ZhihuError::UnsupportedUrl(url.to_string())),
+        }
+    }
+    /// 每页请求的回答数, 默认为知乎允许的最大值 20
+    ///
+    /// 超出范围的值不会报错, 而是截断到 1 到 20 之间: `0` 按 1 请求, 大于 20 的值按 20 请求
+    ///
+    /// 较小的页减少每次请求的数据量, 但需要更多请求
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+    /// [`Self::to_markdown`] 使用的渲染选项
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// [`Self::to_markdown`] 只保留赞同数不少于 `votes` 的回答
+    pub fn min_votes(mut self, votes: usize) -> Self {
+        self.min_votes = votes;
+        self
+    }
+    /// [`Self::to_markdown`] 按赞同数从高到低排列回答, 否则保持知乎的默认顺序
+    pub fn sort_by_votes(mut self, sort_by_votes: bool) -> Self {
+        self.sort_by_votes = sort_by_votes;
+        self
+    }
+    /// 问题 ID
+    pub fn id(&self) -> usize {
+        self.id
+    }
+    /// 获取一页回答, `None` 从第一页开始, 否则从保存的游标处继续
+    pub async fn answers_from_cursor(&self, cursor: Option<&str>) -> ZhihuResult<AnswerPage> {
+        AnswerPage::from_json(&self.feed(cursor, None).await?)
+    }
+    /// 获取问题和全部回答, 合并为一篇 markdown, 问题标题和描述在前, 每个回答注明作者, 以分隔线隔开
+    ///
+    /// 回答按 [`Self::min_votes`] 过滤, 按 [`Self::sort_by_votes`] 排序, [`Self::options`] 开启 frontmatter 时写入问题的元数据
+    pub async fn to_markdown(&self) -> ZhihuResult<String> {
+        let (question, answers) = self.discussion().await?;
+        let title = question["title"].as_str().unwrap_or_default();
+        let description = question["detail"].as_str().unwrap_or_default();
+        write_discussion(&self.options, title, &question_url(self.id), description, &answers)
+    }
+    /// 获取问题和全部回答, 每个回答分别保存到 `dir` 下, 文件名为 `作者-回答ID.md`, 重名时追加序号
+    ///
+    /// 每个文件与 [`Self::to_markdown`] 一样以问题标题和描述开头, 之后是这一个回答, 回答同样按 [`Self::min_votes`] 过滤,
+    /// 按 [`Self::sort_by_votes`] 排序, 返回按此顺序写入的文件
+    pub async fn save_answers<P>(&self, dir: P) -> ZhihuResult<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+    {
+        let (question, answers) = self.discussion().await?;
+        let title = question["title"].as_str().unwrap_or_default();
+        let description = question["detail"].as_str().unwrap_or_default();
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let mut used = HashSet::new();
+        let mut paths = Vec::with_capacity(answers.len());
+        for answer in &answers {
+            let author = sanitize_file_name(&answer.author);
+            let stem = match UrlDispatcher::classify(&answer.url) {
+                Some(ContentKind::Answer { answer, .. }) => format!("{author}-{answer}"),
+                _ => author,
+            };
+            let mut name = format!("{stem}.md");
+            let mut index = 1;
+            // names differing only in case collide on case insensitive file systems
+            while !used.insert(name.to_lowercase()) {
+                index += 1;
+                name = format!("{stem}-{index}.md");
+            }
+            let markdown =
+                write_discussion(&self.options, title, &question_url(self.id), description, std::slice::from_ref(answer))?;
+            let path = dir.join(name);
+            std::fs::write(&path, markdown)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+    /// The json of the question and its answers with their content, filtered and sorted as the options say.
+    async fn discussion(&self) -> ZhihuResult<(Value, Vec<DiscussionAnswer>)> {
+        let question: Value =
+            serde_json::from_str(&self.client.get_text(&self.client.api_url(&format!("/questions/{}", self.id))).await?)?;
+        let mut answers = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let json: Value = serde_json::from_str(&self.feed(cursor.as_deref(), Some(ANSWER_FIELDS)).await?)?;
+            let targets = json["data"].as_array().into_iter().flatten().map(|item| &item["target"]);
+            answers.extend(targets.filter_map(|target| self.discussion_answer(target)));
+            // a cursor that does not move would loop forever
+            match AnswerPage::from_value(&json).next_cursor {
+                Some(next) if cursor.as_deref() != Some(next.as_str()) => cursor = Some(next),
+                _ => break,
+            }
+        }
+        answers.retain(|answer| answer.votes >= self.min_votes);
+        if self.sort_by_votes {
+            answers.sort_by_key(|answer| std::cmp::Reverse(answer.votes));
+        }
+        Ok((question, answers))
+    }
+    /// The raw json of a page of the answer feed, with the extra `include` fields if any.
+    async fn feed(&self, cursor: Option<&str>, include: Option<&str>) -> ZhihuResult<String> {
+        let mut url = url::Url::parse(&self.client.api_url(&format!("/questions/{}/feeds", self.id)))?;
+        url.query_pairs_mut().append_pair("limit", &self.page_size.to_string());
+        if let Some(include) = include {
+            url.query_pairs_mut().append_pair("include", include);
+        }
+        if let Some(cursor) = cursor {
+            url.query_pairs_mut().append_pair("cursor", cursor);
+        }
+        self.client.get_text(url.as_str()).await
+    }
+    /// An answer of the feed with its content, other kinds of feed items are skipped.
+    fn discussion_answer(&self, target: &Value) -> Option<DiscussionAnswer> {
+        if target["type"].as_str().is_some_and(|kind| kind != "answer") {
+            return None;
+        }
+        let id = json_id(&target["id"])?;
+        let author = match target["author"]["name"].as_str() {
+            Some(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => "匿名用户".to_string(),
+        };
+        Some(DiscussionAnswer {
+            author,
+            url: answer_url(self.id, id),
+            votes: target["voteup_count"].as_u64().unwrap_or(0) as usize,
+            content: target["content"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+impl AnswerPage {
+    /// Parse a page of the question feed api, for callers that fetch it themselves.
+    pub fn from_json(json: &str) -> ZhihuResult<Self> {
+        Ok(Self::from_value(&serde_json::from_str(json)?))
+    }
+    fn from_value(json: &Value) -> Self {
+        let answers = json["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|item| &item["target"])
+            .filter(|target| target["type"].as_str().is_none_or(|kind| kind == "answer"))
+            .filter_map(|target| json_id(&target["id"]))
+            .collect();
+        let paging = &json["paging"];
+        let next_cursor = match paging["is_end"].as_bool() {
+            Some(true) => None,
+            _ => paging["next"].as_str().and_then(cursor_of),
+        };
+        Self { answers, next_cursor }
+    }
+    /// 本页回答的 ID
+    pub fn answers(&self) -> &[usize] {
+        &self.answers
+    }
+    /// 下一页的游标, 最后一页时为 `None`
+    pub fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+}
+
+/// The `cursor` query parameter of the next page url.
+fn cursor_of(next: &str) -> Option<String> {
+    let url = url::Url::parse(next).ok()?;
+    url.query_pairs().find(|(k, _)| k == "cursor").map(|(_, cursor)| cursor.into_owned())
+}
@@ -0,0 +1,1439 @@
+use super::{CopyrightPolicy, Engagement, RelatedLink, RenderOptions, mathml::mathml_to_tex};
+use crate::{
+    Column, Comment, ParseMode, ZhihuError, ZhihuResult,
+    utils::{guess_language, normalize_bilibili_image},
+};
+use htmler::{Html, Node, NodeKind, Selector};
+use regex::Regex;
+use std::{
+    borrow::Cow,
+    fmt::{Formatter, Write},
+};
+
+/// Converts the rich text nodes shared by answers and articles to markdown.
+#[derive(Debug)]
+pub(crate) struct MarkdownRenderer<'a> {
+    options: &'a RenderOptions,
+    /// The rendered markdown.
+    pub(crate) content: String,
+    /// Elements that were rendered through their children only.
+    pub(crate) unhandled_tags: Vec<String>,
+    /// Urls of the images referenced by the markdown, in document order.
+    pub(crate) images: Vec<String>,
+    /// References cited in the text, in order of first citation.
+    pub(crate) references: Vec<Reference>,
+    /// How many figure images were rendered so far.
+    figures: usize,
+}
+
+/// A reference cited in the rich text, listed under `## 参考` when [`RenderOptions::references`] is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Reference {
+    pub(crate) number: usize,
+    pub(crate) title: String,
+    pub(crate) url: String,
+}
+
+impl<'a> MarkdownRenderer<'a> {
+    pub(crate) fn new(options: &'a RenderOptions) -> Self {
+        Self { options, content: String::new(), unhandled_tags: vec![], images: vec![], references: vec![], figures: 0 }
+    }
+    /// Render every child of the rich text root.
+    pub(crate) fn render_children(&mut self, root: Node) -> ZhihuResult<()> {
+        for child in root.children() {
+            self.read_content_node(child, NodeContext::default())?;
+        }
+        Ok(())
+    }
+    /// Render a single node.
+    pub(crate) fn render_node(&mut self, node: Node) -> ZhihuResult<()> {
+        self.read_content_node(node, NodeContext::default())
+    }
+    fn read_content_node(&mut self, node: Node, context: NodeContext) -> ZhihuResult<()> {
+        match node.as_kind() {
+            // nothing of these shows in the page
+            NodeKind::Document | NodeKind::Fragment | NodeKind::Doctype(_) | NodeKind::Comment(_) => {}
+            NodeKind::Text(t) => {
+                // keep a single space where the source had whitespace between inline nodes
+                let at_boundary = self.content.is_empty() || self.content.ends_with(char::is_whitespace);
+                if t.starts_with(char::is_whitespace) && !at_boundary {
+                    self.content.push(' ');
+                }
+                // the quotes see the whitespace around them to tell opening from closing
+                let text = self.options.typography.apply(t, self.content.chars().next_back());
+                let trimmed = text.trim();
+                if !trimmed.is_empty() {
+                    // entities were decoded by the parser, do not let markdown decode them a second time
+                    match self.options.raw_html {
+                        true => self.content.push_str(trimmed),
+                        false => self.content.push_str(&escape_html(trimmed)),
+                    }
+                    if t.ends_with(char::is_whitespace) {
+                        self.content.push(' ');
+                    }
+                }
+            }
+            NodeKind::Element(e) => {
+                match e.name() {
+                    // zhihu wraps the text of list items in paragraphs, keep them inside the item
+                    "p" if context.list_depth > 0 => {
+                        if !self.at_item_start() {
+                            self.end_line();
+                            if self.options.loose_lists {
+                                self.content.push('\n');
+                            }
+                            self.content.push_str(&" ".repeat(context.list_indent));
+                        }
+                        for child in node.children() {
+                            self.read_content_node(child, context)?;
+                        }
+                        self.trim_trailing_spaces();
+                    }
+                    // inside a list the rule is indented to stay in the item instead of ending the list
+                    "hr" => self.write_rule(context.list_indent),
+                    "b" | "strong" => {
+                        self.write_styled_span(node, SpanStyle { bold: true, ..SpanStyle::default() }, context)?;
+                    }
+                    "i" | "em" => {
+                        self.write_styled_span(node, SpanStyle { italic: true, ..SpanStyle::default() }, context)?;
+                    }
+                    "del" | "s" | "strike" => {
+                        self.write_styled_span(node, SpanStyle { strike: true, ..SpanStyle::default() }, context)?;
+                    }
+                    "blockquote" => self.write_blockquote(node, context)?,
+                    "p" => match lone_image(node) {
+                        // an image alone in its paragraph is a figure without the wrapper
+                        Some(img) => self.write_picture(img)?,
+                        None => match self.options.update_sections.then(|| section_marker(node)).flatten() {
+                            // a standalone "补充" or "更新" line opens an update section
+                            Some(marker) => {
+                                self.write_rule(0);
+                                write!(self.content, "#### {}\n\n", marker)?;
+                            }
+                            None => {
+                                for child in node.children() {
+                                    self.read_content_node(child, context)?;
+                                }
+                                self.trim_trailing_spaces();
+                                self.content.push_str("\n\n");
+                            }
+                        },
+                    },
+                    "span" => {
+                        // math mode
+                        if e.has_class("ztext-math") {
+                            if let Some(s) = e.get_attribute("data-tex") {
+                                self.write_math(node, s, context)?;
+                            }
+                            else if let Some(math) = node.descendants().find(|n| n.is_a("math")) {
+                                self.write_mathml(node, math, context)?;
+                            }
+                        }
+                        // link card, the nested spans are only decoration
+                        else if e.has_class("RichText-LinkCardContainer") {
+                            if let Some(card) = node.descendants().find(|n| n.is_a("a")) {
+                                self.write_link_card(card)?;
+                            }
+                        }
+                        // stacked spans of bold and colored text
+                        else if self.options.inline_styles && e.has_attribute("style") {
+                            self.write_styled_span(node, span_style(e.get_attribute("style").unwrap_or("")), context)?;
+                        }
+                        // normal mode
+                        else {
+                            for child in node.children() {
+                                self.read_content_node(child, context)?;
+                            }
+                        }
+                    }
+                    // video cards, linking to the zhihu video page
+                    "a" | "div" if e.has_class("video-box") || e.has_class("RichText-video") => {
+                        self.write_video_card(node)?;
+                    }
+                    // a link card outside of its container, as older answers have them
+                    "a" if e.has_class("LinkCard") || e.get_attribute("data-draft-type") == Some("link-card") => {
+                        self.write_link_card(node)?;
+                    }
+                    "a" => {
+                        let start = self.content.len();
+                        for child in node.children() {
+                            self.read_content_node(child, context)?;
+                        }
+                        let text = self.content.split_off(start);
+                        let href = e.get_attribute("href").unwrap_or("");
+                        let link = clean_href(href);
+                        let thumbnail = self.options.video_thumbnails.then(|| video_thumbnail(&link)).flatten();
+                        match (text.trim(), link, thumbnail) {
+                            // internal anchors have nothing to link to in markdown
+                            (text, _, _) if href.is_empty() || href.starts_with('#') => self.content.push_str(text),
+                            (text, href, Some(thumbnail)) => {
+                                write!(self.content, "[![{}]({})]({})", text, thumbnail, href)?;
+                                self.images.push(thumbnail);
+                            }
+                            ("", href, None) => write!(self.content, "<{}>", href)?,
+                            (text, href, None) => write!(self.content, "[{}]({})", text, href)?,
+                        }
+                    }
+                    "ul" | "ol" => {
+                        // a reversed list counts down to 1 unless told where to start
+                        let reversed = e.has_attribute("reversed");
+                        let items = node.children().filter(|n| n.is_a("li")).count();
+                        let start = if reversed { items } else { 1 };
+                        let mut index = e.get_attribute("start").and_then(|s| s.parse().ok()).unwrap_or(start);
+                        let indent = " ".repeat(context.list_indent);
+                        let mut marker_width = 2;
+                        self.end_line();
+                        for item in node.children() {
+                            // a list put right into a list, as some editors do, belongs to the item before it
+                            if item.is_a("ul") || item.is_a("ol") {
+                                let nested = NodeContext {
+                                    list_depth: context.list_depth + 1,
+                                    list_indent: context.list_indent + marker_width,
+                                    ..context
+                                };
+                                self.read_content_node(item, nested)?;
+                                continue;
+                            }
+                            if !item.is_a("li") {
+                                self.read_content_node(item, context)?;
+                                continue;
+                            }
+                            if let Ok(value) = item.get_attribute("value").parse() {
+                                index = value;
+                            }
+                            // each level tracks its own kind, children align with the marker of their parent item
+                            let marker = match e.name() {
+                                "ol" => format!("{}. ", index),
+                                _ => "- ".to_string(),
+                            };
+                            write!(self.content, "{}{}", indent, marker)?;
+                            marker_width = marker.len();
+                            let nested = NodeContext {
+                                list_depth: context.list_depth + 1,
+                                list_indent: context.list_indent + marker.len(),
+                                ..context
+                            };
+                            for child in item.children() {
+                                self.read_content_node(child, nested)?;
+                            }
+                            self.end_line();
+                            index = match reversed {
+                                true => index.saturating_sub(1),
+                                false => index + 1,
+                            };
+                        }
+                        if context.list_depth == 0 {
+                            self.content.push('\n');
+                        }
+                    }
+                    "li" => {
+                        for child in node.children() {
+                            self.read_content_node(child, context)?;
+                        }
+                    }
+                    "pre" => {
+                        let code = preformatted_text(node);
+                        let fence = "`".repeat(longest_backtick_run(&code).max(2) + 1);
+                        self.end_line();
+                        let language = match code_language(node).as_str() {
+                            "" if self.options.guess_language => guess_language(&code).unwrap_or_default().to_string(),
+                            language => language.to_string(),
+                        };
+                        write!(self.content, "{}{}\n{}", fence, language, code)?;
+                        if !code.ends_with('\n') {
+                            self.content.push('\n');
+                        }
+                        write!(self.content, "{}\n\n", fence)?;
+                    }
+                    // the wrapper of highlighted code, the `<pre>` inside carries the code and its language
+                    "div" if e.has_class("highlight") => {
+                        for child in node.children() {
+                            self.read_content_node(child, context)?;
+                        }
+                    }
+                    "code" => {
+                        let code = node.inner_text().replace('\n', " ");
+                        if !code.is_empty() {
+                            self.content.push_str(&code_span(&code));
+                        }
+                    }
+                    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                        self.end_line();
+                        let level = heading_level(node).unwrap_or(1) + self.options.heading_offset;
+                        write!(self.content, "{} ", "#".repeat(level.min(6)))?;
+                        for child in node.children() {
+                            self.read_content_node(child, context)?;
+                        }
+                        self.trim_trailing_spaces();
+                        self.content.push_str("\n\n");
+                    }
+                    "sup" if e.get_attribute("data-draft-type") == Some("reference") => {
+                        let number = match e.get_attribute("data-numero").and_then(|n| n.parse().ok()) {
+                            Some(number) => number,
+                            None => self.references.len() + 1,
+                        };
+                        match self.options.footnotes {
+                            true => write!(self.content, "[^{}]", number)?,
+                            false => write!(self.content, "[{}]", number)?,
+                        }
+                        if !self.references.iter().any(|r| r.number == number) {
+                            let title = e.get_attribute("data-text").unwrap_or("").trim().to_string();
+                            let url = e.get_attribute("data-url").unwrap_or("").trim().to_string();
+                            self.references.push(Reference { number, title, url });
+                        }
+                    }
+                    // a run of breaks separates paragraphs, a single one only breaks the line
+                    "br" => match break_run(node) {
+                        Some(1) => self.write_line_break(context),
+                        Some(_) if context.list_depth > 0 => self.write_line_break(context),
+                        Some(_) => {
+                            self.end_line();
+                            if !self.content.is_empty() && !self.content.ends_with("\n\n") {
+                                self.content.push('\n');
+                            }
+                        }
+                        None => {}
+                    },
+                    "figure" => {
+                        let caption = match node.descendants().find(|n| n.is_a("figcaption")) {
+                            Some(caption) => caption.inner_text(),
+                            None => String::new(),
+                        };
+                        if let Some(video) = node.descendants().find(|n| n.is_a("video")) {
+                            self.write_video(video, caption.trim())?;
+                        }
+                        else if let Some((img, src)) = node
+                            .descendants()
+                            .filter(|n| n.is_a("img"))
+                            .find_map(|img| Some((img, image_source(img, self.options.modern_images)?)))
+                        {
+                            self.write_figure(img, src, caption.trim())?;
+                        }
+                    }
+                    // legacy formulas are images of the rendered latex
+                    "img" if e.get_attribute("eeimg").is_some() => {
+                        if let Some(tex) = eeimg_tex(node) {
+                            self.write_math(node, &tex, context)?;
+                        }
+                    }
+                    "img" => {
+                        if let Some(src) = image_source(node, self.options.modern_images) {
+                            let gif = animated_source(node);
+                            let src = normalize_bilibili_image(gif.as_deref().unwrap_or(src));
+                            write!(self.content, "![{}]({})", img_alt(node), src)?;
+                            self.images.push(src.to_string());
+                        }
+                    }
+                    "video" => {
+                        self.write_video(node, "")?;
+                    }
+                    // the player of a gif, only the image inside matters, the play button is decoration
+                    "div" if e.has_class("RichText-gifPlaceholder") || e.has_class("GifPlayer") => {
+                        if let Some(img) = node.descendants().find(|n| n.is_a("img")) {
+                            self.write_picture(img)?;
+                        }
+                    }
+                    "table" => self.write_table(node, context)?,
+                    "math" => self.write_mathml(node, node, context)?,
+                    "picture" => {
+                        if let Some(img) = node.children().find(|n| n.is_a("img")) {
+                            self.write_picture(img)?;
+                        }
+                    }
+                    unknown => {
+                        if self.options.mode == ParseMode::Strict {
+                            return Err(ZhihuError::UnknownElement(unknown.to_string()));
+                        }
+                        if !self.unhandled_tags.iter().any(|tag| tag == unknown) {
+                            self.unhandled_tags.push(unknown.to_string());
+                        }
+                        for child in node.children() {
+                            self.read_content_node(child, context)?;
+                        }
+                    }
+                }
+            }
+            NodeKind::ProcessingInstruction(_) => {}
+        }
+        Ok(())
+    }
+    /// Render a link card as a plain link titled by the card, wrapping its thumbnail under
+    /// [`RenderOptions::link_card_images`].
+    fn write_link_card(&mut self, card: Node) -> ZhihuResult<()> {
+        let href = unwrap_redirect(card.get_attribute("href"));
+        if href.is_empty() {
+            return Ok(());
+        }
+        let title = match card.descendants().find(|n| n.as_data().is_some_and(|e| e.has_class("LinkCard-title"))) {
+            Some(title) => title.inner_text(),
+            None => card.get_attribute("data-text").to_string(),
+        };
+        let image = card
+            .descendants()
+            .find(|n| n.is_a("img"))
+            .map_or(card.get_attribute("data-image"), |img| img.get_attribute("src"))
+            .trim();
+        match title.trim() {
+            title if self.options.link_card_images && !image.is_empty() => {
+                write!(self.content, "[![{}]({})]({})", title, image, href)?;
+                self.images.push(image.to_string());
+            }
+            "" => write!(self.content, "<{}>", href)?,
+            title => write!(self.content, "[{}]({})", title, href)?,
+        }
+        Ok(())
+    }
+    /// Write a formula displayed when it stands alone in its paragraph or ends with the `\\` zhihu's editor marks
+    /// block formulas with, inline otherwise.
+    fn write_math(&mut self, node: Node, tex: &str, context: NodeContext) -> ZhihuResult<()> {
+        let (tex, marked) = match tex.trim_end().strip_suffix("\\\\") {
+            Some(tex) => (tex.trim_end(), true),
+            None => (tex, false),
+        };
+        let (inline, display) = self.options.math_delimiters.pair();
+        // inside a list the lines of a block formula are indented to stay in the item
+        let indent = " ".repeat(context.list_indent);
+        let block = format!("{}\n{}\n{}", display.0, tex, display.1).replace('\n', &format!("\n{indent}"));
+        if is_display_math(node) {
+            self.content.push_str(&block);
+        }
+        else if marked {
+            // a block formula amid text gets lines of its own
+            self.end_line();
+            write!(self.content, "{indent}{block}\n{indent}")?;
+        }
+        else {
+            if !self.content.is_empty() && !self.content.ends_with(char::is_whitespace) {
+                self.content.push(' ');
+            }
+            write!(self.content, "{}{}{}", inline.0, tex, inline.1)?;
+            // only text that would run into the closing delimiter is spaced off
+            if next_char(node).is_some_and(|c| !c.is_whitespace() && !is_punctuation(c)) {
+                self.content.push(' ');
+            }
+        }
+        Ok(())
+    }
+    /// Write a MathML formula as latex, or verbatim with [`RenderOptions::raw_mathml`].
+    fn write_mathml(&mut self, node: Node, math: Node, context: NodeContext) -> ZhihuResult<()> {
+        match self.options.raw_mathml {
+            true => self.content.push_str(&math.html()),
+            false => self.write_math(node, &mathml_to_tex(math), context)?,
+        }
+        Ok(())
+    }
+    /// Zhihu serves gifs as looping muted `<video>`, render the poster linking to the video.
+    fn write_video(&mut self, video: Node, caption: &str) -> ZhihuResult<()> {
+        let src = match video.get_attribute("src") {
+            "" => video.descendants().find(|n| n.is_a("source")).map_or("", |n| n.get_attribute("src")),
+            src => src,
+        };
+        let poster = match video.get_attribute("data-poster") {
+            "" => video.get_attribute("poster"),
+            poster => poster,
+        };
+        let animated = video.has_attribute("loop") || video.has_attribute("muted");
+        let label = match (caption, animated) {
+            ("", true) => "动图",
+            ("", false) => "视频",
+            (caption, _) => caption,
+        };
+        self.end_line();
+        match (poster, src) {
+            ("", "") => return Ok(()),
+            ("", src) => write!(self.content, "[{}]({})", label, src)?,
+            (poster, "") => write!(self.content, "![{}]({})", label, poster)?,
+            (poster, src) => write!(self.content, "[![{}]({})]({})", label, poster, src)?,
+        }
+        self.content.push_str("\n\n");
+        if !poster.is_empty() {
+            self.images.push(poster.to_string());
+        }
+        Ok(())
+    }
+    /// Render a video card as its cover linking to the video, titled by the card.
+    ///
+    /// The older `a.video-box` carries everything in data attributes, the newer `div.RichText-video` in the
+    /// `data-za-extra-module` json.
+    fn write_video_card(&mut self, card: Node) -> ZhihuResult<()> {
+        let extra = serde_json::from_str::<serde_json::Value>(card.get_attribute("data-za-extra-module")).ok();
+        let video_id = extra
+            .as_ref()
+            .and_then(|extra| extra.pointer("/card/content/video_id"))
+            .and_then(|id| id.as_str().map(str::to_string).or_else(|| id.as_u64().map(|id| id.to_string())));
+        let href = match (card.get_attribute("href"), card.get_attribute("data-lens-id")) {
+            ("", "") => video_id.map(|id| format!("https://www.zhihu.com/video/{}", id)).unwrap_or_default(),
+            ("", id) => format!("https://www.zhihu.com/video/{}", id),
+            (href, _) => clean_href(href).into_owned(),
+        };
+        let first = |class: &str| {
+            card.descendants()
+                .find(|n| n.as_data().is_some_and(|e| e.has_class(class)))
+                .map(|n| n.children().filter_map(|n| n.as_text().map(|text| text.to_string())).collect::<String>())
+        };
+        let title = match card.get_attribute("data-name").trim() {
+            "" => first("title").unwrap_or_default(),
+            name => name.to_string(),
+        };
+        let title = match title.trim() {
+            "" => "视频",
+            title => title,
+        };
+        let cover = match card.get_attribute("data-poster") {
+            "" => card.descendants().find(|n| n.is_a("img")).map_or("", |img| img.get_attribute("src")),
+            poster => poster,
+        };
+        self.end_line();
+        match (cover, href.as_str()) {
+            ("", "") => return Ok(()),
+            ("", href) => write!(self.content, "[{}]({})", title, href)?,
+            (cover, "") => write!(self.content, "![{}]({})", title, cover)?,
+            (cover, href) => write!(self.content, "[![{}]({})]({})", title, cover, href)?,
+        }
+        self.content.push_str("\n\n");
+        if !cover.is_empty() {
+            self.images.push(cover.to_string());
+        }
+        Ok(())
+    }
+    /// Write the content of a styled span or an emphasis element, emphasis that an enclosing one already opened is not
+    /// repeated.
+    fn write_styled_span(&mut self, span: Node, style: SpanStyle, context: NodeContext) -> ZhihuResult<()> {
+        let nested = NodeContext {
+            bold: context.bold || style.bold,
+            italic: context.italic || style.italic,
+            strike: context.strike || style.strike,
+            ..context
+        };
+        let start = self.content.len();
+        for child in span.children() {
+            self.read_content_node(child, nested)?;
+        }
+        let text = self.content.split_off(start);
+        let after = next_char(span);
+        // emphasis cannot span paragraphs, each one gets its own
+        let blocks = text.split("\n\n").count();
+        for (i, block) in text.split("\n\n").enumerate() {
+            if i > 0 {
+                self.content.push_str("\n\n");
+            }
+            // emphasis must hug the text, the surrounding whitespace stays outside of it
+            let trimmed = block.trim();
+            if trimmed.is_empty() {
+                self.content.push_str(block);
+                continue;
+            }
+            self.content.push_str(&block[..block.len() - block.trim_start().len()]);
+            match opens_block(trimmed) {
+                true => self.content.push_str(trimmed),
+                false => {
+                    let before = self.content.chars().next_back();
+                    let after = if i + 1 == blocks { after } else { None };
+                    let styled = style.wrap(trimmed, context, before, after);
+                    self.content.push_str(&styled);
+                }
+            }
+            self.content.push_str(&block[block.trim_end().len()..]);
+        }
+        Ok(())
+    }
+    /// Write a hard line break, continuing at the indentation of the enclosing list item.
+    fn write_line_break(&mut self, context: NodeContext) {
+        self.trim_trailing_spaces();
+        if self.content.is_empty() || self.content.ends_with('\n') {
+            return;
+        }
+        self.content.push_str("\\\n");
+        self.content.push_str(&" ".repeat(context.list_indent));
+    }
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.content.trim_end_matches(' ').len();
+        self.content.truncate(trimmed);
+    }
+    /// Write an image without caption as a block of its own.
+    pub(crate) fn write_picture(&mut self, img: Node) -> ZhihuResult<()> {
+        match image_source(img, self.options.modern_images) {
+            Some(src) => self.write_figure(img, src, ""),
+            None => Ok(()),
+        }
+    }
+    /// Write an image as a block of its own, with the caption as alt text or title, or as an italic line beneath it
+    /// under [`RenderOptions::caption_lines`].
+    fn write_figure(&mut self, img: Node, src: &str, caption: &str) -> ZhihuResult<()> {
+        let (caption, line) = match self.options.caption_lines {
+            true => ("", caption),
+            false => (caption, ""),
+        };
+        let gif = animated_source(img);
+        let src = normalize_bilibili_image(gif.as_deref().unwrap_or(src));
+        self.figures += 1;
+        let fallback = match &self.options.numbered_alt {
+            Some(prefix) => format!("{}{}", prefix, self.figures),
+            None => String::new(),
+        };
+        let alt = match (img_alt(img), caption) {
+            ("", "") => fallback.as_str(),
+            ("", caption) => caption,
+            (alt, _) => alt,
+        };
+        // the caption, or the hover text, goes to the title slot when it adds something to the alt
+        let title = [caption, img.get_attribute("title").trim()].into_iter().find(|title| !title.is_empty() && *title != alt);
+        self.end_line();
+        match title {
+            Some(title) => write!(self.content, "![{}]({} \"{}\")\n\n", alt, src, title.replace('"', "\\\""))?,
+            None => write!(self.content, "![{}]({})\n\n", alt, src)?,
+        }
+        if !line.is_empty() {
+            write!(self.content, "*{}*\n\n", line.replace('*', "\\*"))?;
+        }
+        self.images.push(src.to_string());
+        Ok(())
+    }
+    /// Write a GFM table, the first row is the header and the alignment of a column comes from its first aligned cell.
+    fn write_table(&mut self, table: Node, context: NodeContext) -> ZhihuResult<()> {
+        let mut rows: Vec<Vec<String>> = vec![];
+        let mut aligns: Vec<Option<&str>> = vec![];
+        // pipe tables cannot merge cells, a spanning cell keeps its text in the first and leaves the rest empty
+        let mut spanned: Vec<usize> = vec![];
+        for row in table.descendants().filter(|n| n.is_a("tr")) {
+            let mut cells: Vec<String> = vec![];
+            for cell in row.children().filter(|n| n.is_a("th") || n.is_a("td")) {
+                skip_spanned(&mut cells, &mut spanned);
+                let start = self.content.len();
+                for child in cell.children() {
+                    self.read_content_node(child, context)?;
+                }
+                let text = self.content.split_off(start);
+                let column = cells.len();
+                let span = |name: &str| cell.get_attribute(name).parse::<usize>().map_or(1, |span| span.clamp(1, 100));
+                let (columns, rows) = (span("colspan"), span("rowspan"));
+                cells.push(text.trim().replace('|', "\\|").replace("\\\n", "\n").replace('\n', "<br>"));
+                cells.resize(column + columns, String::new());
+                if spanned.len() < cells.len() {
+                    spanned.resize(cells.len(), 0);
+                }
+                spanned[column..].iter_mut().take(columns).for_each(|left| *left = rows - 1);
+                if aligns.len() < cells.len() {
+                    aligns.resize(cells.len(), None);
+                }
+                aligns[column] = aligns[column].or_else(|| cell_align(cell));
+            }
+            skip_spanned(&mut cells, &mut spanned);
+            if aligns.len() < cells.len() {
+                aligns.resize(cells.len(), None);
+            }
+            rows.push(cells);
+        }
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.end_line();
+        if !self.content.is_empty() && !self.content.ends_with("\n\n") {
+            self.content.push('\n');
+        }
+        for (index, row) in rows.iter().enumerate() {
+            for column in 0..aligns.len() {
+                write!(self.content, "| {} ", row.get(column).map_or("", |cell| cell.as_str()))?;
+            }
+            self.content.push_str("|\n");
+            if index == 0 {
+                for align in &aligns {
+                    let rule = match align {
+                        Some("left") => ":---",
+                        Some("center") => ":---:",
+                        Some("right") => "---:",
+                        _ => "---",
+                    };
+                    write!(self.content, "| {} ", rule)?;
+                }
+                self.content.push_str("|\n");
+            }
+        }
+        self.content.push('\n');
+        Ok(())
+    }
+    /// Write a quote as `> ` prefixed lines, a nested quote gets the prefix of every enclosing one.
+    fn write_blockquote(&mut self, quote: Node, context: NodeContext) -> ZhihuResult<()> {
+        let item_start = context.list_depth > 0 && self.at_item_start();
+        if !item_start {
+            self.end_line();
+            if context.list_depth == 0 && !self.content.is_empty() && !self.content.ends_with("\n\n") {
+                self.content.push('\n');
+            }
+        }
+        // the quote is rendered as a document of its own, then indented under the item it belongs to
+        let start = self.content.len();
+        let inner = NodeContext { list_depth: 0, list_indent: 0, ..context };
+        for child in quote.children() {
+            self.read_content_node(child, inner)?;
+        }
+        let quoted = self.content.split_off(start);
+        let indent = " ".repeat(context.list_indent);
+        for (index, line) in quoted.trim().lines().enumerate() {
+            if index > 0 || !item_start {
+                self.content.push_str(&indent);
+            }
+            match line.is_empty() {
+                true => self.content.push('>'),
+                false => write!(self.content, "> {}", line)?,
+            }
+            self.content.push('\n');
+        }
+        if context.list_depth == 0 {
+            self.content.push('\n');
+        }
+        Ok(())
+    }
+    /// Write a thematic break indented by `indent` columns, unless the previous block already is one.
+    fn write_rule(&mut self, indent: usize) {
+        if self.content.trim_end_matches(' ').ends_with("---\n\n") {
+            return;
+        }
+        self.end_line();
+        // a blank line keeps `---` from turning the previous paragraph into a heading
+        if !self.content.is_empty() && !self.content.ends_with("\n\n") {
+            self.content.push('\n');
+        }
+        self.content.push_str(&" ".repeat(indent));
+        self.content.push_str("---\n\n");
+        // the rest of the item continues at its indentation, trimmed again if nothing follows
+        self.content.push_str(&" ".repeat(indent));
+    }
+    /// Whether nothing but the marker of a list item was written on the current line.
+    fn at_item_start(&self) -> bool {
+        let line = self.content.rsplit('\n').next().unwrap_or_default().trim_start();
+        match line.strip_suffix(". ") {
+            Some(index) => !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()),
+            None => line == "- ",
+        }
+    }
+    /// Make sure the next output starts on a new line, dropping trailing spaces.
+    fn end_line(&mut self) {
+        self.trim_trailing_spaces();
+        if !self.content.is_empty() && !self.content.ends_with('\n') {
+            self.content.push('\n');
+        }
+    }
+}
+
+/// Write the final document: optional frontmatter, the title heading, the body, the optional related appendix and citation footer,
+/// then hand it to the user's post process hook if any.
+pub(crate) fn write_document(f: &mut Formatter<'_>, options: &RenderOptions, document: &Document) -> std::fmt::Result {
+    match &options.post_process {
+        Some(hook) => {
+            let mut markdown = String::new();
+            write_markdown(&mut markdown, options, document)?;
+            f.write_str(&hook(markdown))
+        }
+        None => write_markdown(f, options, document),
+    }
+}
+
+/// Render the first node of the rich text `source` matching `selector`.
+///
+/// A heading takes its section along, every following sibling up to the next heading of the same or a higher level.
+pub(crate) fn render_section(options: &RenderOptions, source: &str, selector: &Selector) -> ZhihuResult<Option<String>> {
+    let html = Html::parse_fragment(source);
+    let Some(start) = html.select(selector).next()
+    else {
+        return Ok(None);
+    };
+    let mut renderer = MarkdownRenderer::new(options);
+    renderer.render_node(start)?;
+    if let Some(level) = heading_level(start) {
+        for sibling in start.next_siblings() {
+            if heading_level(sibling).is_some_and(|l| l <= level) {
+                break;
+            }
+            renderer.render_node(sibling)?;
+        }
+    }
+    Ok(Some(renderer.content))
+}
+
+/// Render a rich text fragment as it is, without any of the document around it.
+#[cfg(feature = "network")]
+fn render_fragment(options: &RenderOptions, source: &str) -> ZhihuResult<String> {
+    let html = Html::parse_fragment(source);
+    // the parser wraps the fragment in an `<html>` element
+    let root = html.root_node().children().find(|n| n.is_a("html")).unwrap_or(html.root_node());
+    let mut renderer = MarkdownRenderer::new(options);
+    renderer.render_children(root)?;
+    Ok(renderer.content)
+}
+
+/// One answer of a question archived as a whole discussion.
+#[cfg(feature = "network")]
+pub(crate) struct DiscussionAnswer {
+    pub(crate) author: String,
+    pub(crate) url: String,
+    pub(crate) votes: usize,
+    /// The rich text of the answer.
+    pub(crate) content: String,
+}
+
+/// Write a question and its answers as one document: optional frontmatter, the title, the description,
+/// then every answer under the name of its author, separated by rules.
+#[cfg(feature = "network")]
+pub(crate) fn write_discussion(
+    options: &RenderOptions,
+    title: &str,
+    url: &str,
+    description: &str,
+    answers: &[DiscussionAnswer],
+) -> ZhihuResult<String> {
+    let mut f = String::new();
+    if options.frontmatter {
+        let format = options.frontmatter_format;
+        writeln!(f, "{}", format.delimiter())?;
+        format.write_field(&mut f, "title", &super::quote_string(title))?;
+        format.write_field(&mut f, "url", &super::quote_string(url))?;
+        format.write_field(&mut f, "answers", &answers.len())?;
+        writeln!(f, "{}\n", format.delimiter())?;
+    }
+    if !title.is_empty() {
+        write!(f, "# {}\n\n", title)?;
+    }
+    let description = render_fragment(options, description)?;
+    if !description.trim().is_empty() {
+        writeln!(f, "{}", description.trim_end())?;
+    }
+    for answer in answers {
+        write!(f, "\n---\n\n## {}\n\n> 赞同 {} · <{}>\n\n", answer.author, answer.votes, answer.url)?;
+        writeln!(f, "{}", render_fragment(options, &answer.content)?.trim_end())?;
+    }
+    Ok(match &options.post_process {
+        Some(hook) => hook(f),
+        None => f,
+    })
+}
+
+/// `Some(n)` for a `<hn>` element.
+fn heading_level(node: Node<'_>) -> Option<usize> {
+    let name = node.as_data()?.name();
+    match name.strip_prefix('h')?.parse() {
+        Ok(level @ 1..=6) => Some(level),
+        _ => None,
+    }
+}
+
+/// The parts of an answer or an article that make up the final document.
+pub(crate) struct Document<'a> {
+    pub(crate) title: &'a str,
+    /// Markdown quoted under the title, such as the description of the question.
+    pub(crate) intro: Option<&'a str>,
+    /// A quoted line under the title, such as the author.
+    pub(crate) byline: Option<&'a str>,
+    /// The name of the author, for the frontmatter.
+    pub(crate) author: Option<&'a str>,
+    /// The topics the content is filed under.
+    pub(crate) topics: &'a [String],
+    pub(crate) content: &'a str,
+    pub(crate) permalink: Option<&'a str>,
+    pub(crate) related: &'a [RelatedLink],
+    pub(crate) references: &'a [Reference],
+    /// The column an article belongs to.
+    pub(crate) column: Option<&'a Column>,
+    /// The counts written to the frontmatter under [`RenderOptions::engagement`].
+    pub(crate) engagement: Engagement,
+    /// When the content was published, as zhihu shows it.
+    pub(crate) published: Option<&'a str>,
+    /// When the content was last edited, as zhihu shows it.
+    pub(crate) edited: Option<&'a str>,
+    /// When the page was fetched, in seconds since the unix epoch.
+    pub(crate) fetched_at: Option<u64>,
+    /// Whether the author forbids reposting.
+    pub(crate) repost_forbidden: bool,
+    pub(crate) comments: &'a [Comment],
+}
+
+fn write_markdown<W: Write>(f: &mut W, options: &RenderOptions, document: &Document) -> std::fmt::Result {
+    let Document {
+        title,
+        intro,
+        byline,
+        author,
+        topics,
+        content,
+        permalink,
+        related,
+        references,
+        column,
+        engagement,
+        published,
+        edited,
+        fetched_at,
+        repost_forbidden,
+        comments,
+    } = *document;
+    let content = strip_boilerplate(content, &options.trailing_boilerplate);
+    if options.frontmatter {
+        let format = options.frontmatter_format;
+        writeln!(f, "{}", format.delimiter())?;
+        format.write_field(f, "title", &super::quote_string(title))?;
+        format.write_field(f, "reading_minutes", &crate::utils::reading_minutes(content))?;
+        if let Some(author) = author {
+            format.write_field(f, "author", &super::quote_string(author))?;
+        }
+        if let Some(link) = permalink {
+            format.write_field(f, "url", &super::quote_string(link))?;
+        }
+        if !topics.is_empty() {
+            // a flow sequence of quoted strings reads the same in YAML and TOML
+            let tags: Vec<_> = topics.iter().map(|topic| super::quote_string(topic)).collect();
+            format.write_field(f, "tags", &format_args!("[{}]", tags.join(", ")))?;
+        }
+        if let Some(column) = column {
+            format.write_field(f, "column", &super::quote_string(&column.name))?;
+            format.write_field(f, "column_url", &super::quote_string(&column.url))?;
+        }
+        if let Some(date) = published {
+            format.write_date(f, "date", date)?;
+        }
+        if let Some(date) = edited {
+            format.write_date(f, "updated", date)?;
+        }
+        if options.engagement {
+            let counts =
+                [("votes", engagement.votes), ("comment_count", engagement.comments), ("favorites", engagement.favorites)];
+            for (key, count) in counts {
+                if let Some(count) = count {
+                    format.write_field(f, key, &count)?;
+                }
+            }
+        }
+        writeln!(f, "{}\n", format.delimiter())?;
+    }
+    // after the frontmatter, which has to open the file
+    if options.provenance {
+        f.write_str("<!-- ")?;
+        if let Some(link) = permalink {
+            write!(f, "source: {} ", link)?;
+        }
+        if let Some(fetched_at) = fetched_at {
+            write!(f, "fetched: {} ", utc_timestamp(fetched_at))?;
+        }
+        write!(f, "generator: zhihu-link v{} -->\n\n", env!("CARGO_PKG_VERSION"))?;
+    }
+    // a page without any title gets no heading rather than a bare `# `
+    if !title.is_empty() {
+        write!(f, "# {}\n\n", title)?;
+    }
+    if let Some(intro) = intro.filter(|intro| !intro.trim().is_empty()) {
+        for line in intro.trim().lines() {
+            match line.is_empty() {
+                true => f.write_str(">\n")?,
+                false => writeln!(f, "> {}", line)?,
+            }
+        }
+        f.write_char('\n')?;
+    }
+    if let Some(byline) = byline {
+        write!(f, "> {}\n\n", byline)?;
+    }
+    if repost_forbidden && options.copyright != CopyrightPolicy::Ignore {
+        f.write_str("> 著作权归作者所有, 作者设置了禁止转载\n\n")?;
+    }
+    let cited = (options.references || options.footnotes) && !references.is_empty();
+    let appendix = options.related && !related.is_empty();
+    let citation = permalink.filter(|_| options.citation);
+    let edited = edited.filter(|_| options.edit_note);
+    if !cited && !appendix && citation.is_none() && edited.is_none() && comments.is_empty() {
+        return f.write_str(content);
+    }
+    writeln!(f, "{}", content.trim_end())?;
+    if let Some(date) = edited {
+        write!(f, "\n*编辑于 {}*\n", date)?;
+    }
+    if !comments.is_empty() {
+        write!(f, "\n## 评论\n")?;
+        for comment in comments {
+            f.write_char('\n')?;
+            write_comment(f, comment, 1)?;
+        }
+    }
+    if cited && options.footnotes {
+        // every marker needs a definition, even one without title nor url
+        f.write_char('\n')?;
+        for reference in references {
+            match (reference.title.as_str(), reference.url.as_str()) {
+                ("", "") => writeln!(f, "[^{0}]: 参考文献 {0}", reference.number)?,
+                (title, "") => writeln!(f, "[^{}]: {}", reference.number, title)?,
+                ("", url) => writeln!(f, "[^{}]: <{}>", reference.number, url)?,
+                (title, url) => writeln!(f, "[^{}]: [{}]({})", reference.number, title, url)?,
+            }
+        }
+    }
+    else if cited {
+        write!(f, "\n## 参考\n\n")?;
+        for reference in references {
+            match (reference.title.as_str(), reference.url.as_str()) {
+                ("", "") => continue,
+                (title, "") => writeln!(f, "{}. {}", reference.number, title)?,
+                ("", url) => writeln!(f, "{}. <{}>", reference.number, url)?,
+                (title, url) => writeln!(f, "{}. [{}]({})", reference.number, title, url)?,
+            }
+        }
+    }
+    if appendix {
+        write!(f, "\n## 相关推荐\n\n")?;
+        for link in related {
+            writeln!(f, "- [{}]({})", link.title, link.url)?;
+        }
+    }
+    if let Some(link) = citation {
+        write!(f, "\n> 原文链接: <{}>\n", link)?;
+    }
+    Ok(())
+}
+
+/// Write a comment as a blockquote `depth` levels deep, its replies nested one level deeper.
+fn write_comment<W: Write>(f: &mut W, comment: &Comment, depth: usize) -> std::fmt::Result {
+    let quote = "> ".repeat(depth);
+    let mut lines = comment.content.lines();
+    writeln!(f, "{}**{}**: {}", quote, comment.author, lines.next().unwrap_or_default())?;
+    for line in lines {
+        writeln!(f, "{}{}", quote, line)?;
+    }
+    for reply in &comment.replies {
+        writeln!(f, "{}", quote.trim_end())?;
+        write_comment(f, reply, depth + 1)?;
+    }
+    Ok(())
+}
+
+/// Leave the cells covered by a `rowspan` of an earlier row empty, counting down the rows they still span.
+fn skip_spanned(cells: &mut Vec<String>, spanned: &mut [usize]) {
+    while spanned.get(cells.len()).is_some_and(|rows| *rows > 0) {
+        spanned[cells.len()] -= 1;
+        cells.push(String::new());
+    }
+}
+
+/// The alignment of a table cell, from its `align` attribute or a `text-align` style.
+fn cell_align(cell: Node<'_>) -> Option<&'static str> {
+    let style = cell.get_attribute("style");
+    let declared = style
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .find(|(property, _)| property.trim().eq_ignore_ascii_case("text-align"))
+        .map_or(cell.get_attribute("align"), |(_, value)| value);
+    match declared.trim().to_ascii_lowercase().as_str() {
+        "left" => Some("left"),
+        "center" => Some("center"),
+        "right" => Some("right"),
+        _ => None,
+    }
+}
+
+/// The length of the run of line breaks that `br` opens, `None` when an earlier break of the run
+/// was already written or nothing but breaks and whitespace follows.
+fn break_run(br: Node<'_>) -> Option<usize> {
+    let parent = match br.parent() {
+        Some(parent) => parent,
+        None => return Some(1),
+    };
+    let siblings: Vec<Node> = parent
+        .children()
+        .filter(|child| {
+            !child.as_text().is_some_and(|t| t.trim().is_empty()) && !matches!(child.as_kind(), NodeKind::Comment(_))
+        })
+        .collect();
+    let index = siblings.iter().position(|child| *child == br)?;
+    if index > 0 && siblings[index - 1].is_a("br") {
+        return None;
+    }
+    let run = siblings[index..].iter().take_while(|child| child.is_a("br")).count();
+    match index + run < siblings.len() {
+        true => Some(run),
+        false => None,
+    }
+}
+
+/// The image of a paragraph that holds nothing else but whitespace and line breaks.
+fn lone_image(p: Node<'_>) -> Option<Node<'_>> {
+    let mut content = p.children().filter(|child| {
+        !child.is_a("br")
+            && !child.as_text().is_some_and(|t| t.trim().is_empty())
+            && !matches!(child.as_kind(), NodeKind::Comment(_))
+    });
+    match (content.next(), content.next()) {
+        (Some(img), None) if img.is_a("img") && !img.has_attribute("eeimg") => Some(img),
+        (Some(picture), None) if picture.is_a("picture") => picture.children().find(|n| n.is_a("img")),
+        _ => None,
+    }
+}
+
+fn img_alt(img: Node<'_>) -> &str {
+    img.get_attribute("alt").trim()
+}
+
+/// The short label of a paragraph that introduces an update, such as `补充说明:` or `更新 2023.5.1`.
+fn section_marker(node: Node) -> Option<String> {
+    const MARKERS: [&str; 6] = ["补充", "更新", "追加", "编辑", "原回答", "update"];
+    let text = node.inner_text();
+    let label = text.trim().trim_end_matches([':', '：']).trim_end();
+    let known = MARKERS.iter().any(|marker| label.to_lowercase().starts_with(marker));
+    match known && label.chars().count() <= 20 && !label.contains(['。', '，', ',']) {
+        true => Some(label.to_string()),
+        false => None,
+    }
+}
+
+/// Cut the trailing lines matching any of `patterns`, such as a closing "欢迎关注" plea, with the blank lines before them.
+fn strip_boilerplate<'a>(content: &'a str, patterns: &[Regex]) -> &'a str {
+    if patterns.is_empty() {
+        return content;
+    }
+    let mut kept = content;
+    loop {
+        let body = kept.trim_end();
+        let start = body.rfind('\n').map_or(0, |i| i + 1);
+        let line = body[start..].trim();
+        match !line.is_empty() && patterns.iter().any(|pattern| pattern.is_match(line)) {
+            true => kept = &body[..start],
+            false => return kept,
+        }
+    }
+}
+
+/// Format seconds since the unix epoch as `YYYY-MM-DDTHH:MM:SSZ`.
+fn utc_timestamp(secs: u64) -> String {
+    let (days, time) = (secs / 86400, secs % 86400);
+    // the civil from days algorithm of Howard Hinnant, with eras of 400 years
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z", time / 3600, time / 60 % 60, time % 60)
+}
+
+/// Nesting state threaded through the content reader.
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct NodeContext {
+    /// How many lists enclose the node.
+    pub(crate) list_depth: usize,
+    /// The column where the content of the enclosing list item starts.
+    pub(crate) list_indent: usize,
+    /// Whether an enclosing span is already written bold.
+    pub(crate) bold: bool,
+    /// Whether an enclosing span is already written italic.
+    pub(crate) italic: bool,
+    /// Whether an enclosing span is already struck through.
+    pub(crate) strike: bool,
+}
+
+/// The formatting a span declares in its inline `style`, or an emphasis element implies.
+#[derive(Debug, Default)]
+struct SpanStyle<'a> {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    color: Option<&'a str>,
+}
+
+impl SpanStyle<'_> {
+    /// Wrap `text` in the emphasis that `context` has not opened yet, as html tags when markers between `before` and
+    /// `after` would not be taken for emphasis.
+    fn wrap(&self, text: &str, context: NodeContext, before: Option<char>, after: Option<char>) -> String {
+        let mut styled = text.to_string();
+        if let Some(color) = self.color {
+            styled = format!("<span style=\"color: {}\">{}</span>", color, styled);
+        }
+        let markers = markers_flank(before, &styled, after);
+        let emphasis = [
+            (self.italic && !context.italic, "*", "em"),
+            (self.bold && !context.bold, "**", "strong"),
+            (self.strike && !context.strike, "~~", "del"),
+        ];
+        for (_, marker, tag) in emphasis.into_iter().filter(|(open, ..)| *open) {
+            styled = match markers {
+                true => format!("{marker}{styled}{marker}"),
+                false => format!("<{tag}>{styled}</{tag}>"),
+            };
+        }
+        styled
+    }
+}
+
+/// Whether emphasis markers around `text` open and close between `before` and `after`.
+///
+/// A marker between a letter and punctuation does not, so `中**“强调”**文` is not bold.
+fn markers_flank(before: Option<char>, text: &str, after: Option<char>) -> bool {
+    let loose = |c: Option<char>| c.is_none_or(|c| c.is_whitespace() || is_punctuation(c));
+    let opens = !text.starts_with(is_punctuation) || loose(before);
+    let closes = !text.ends_with(is_punctuation) || loose(after);
+    opens && closes
+}
+
+/// Ascii punctuation and the common punctuation of CJK and western text.
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c, '¡' | '«' | '·' | '»' | '¿' | '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205E}' | '\u{3001}'..='\u{3003}'
+            | '\u{3008}'..='\u{3011}' | '\u{3014}'..='\u{301F}' | '\u{FF01}'..='\u{FF0F}' | '\u{FF1A}'..='\u{FF20}'
+            | '\u{FF3B}'..='\u{FF40}' | '\u{FF5B}'..='\u{FF65}')
+}
+
+/// The first character following `node` within its parent, `None` at the end.
+fn next_char(node: Node) -> Option<char> {
+    node.next_siblings().find_map(|sibling| match sibling.as_text() {
+        Some(text) => text.chars().next(),
+        None => sibling.inner_text().chars().next(),
+    })
+}
+
+/// Whether a rendered block starts with the syntax of a heading, quote, list, table or fence, which emphasis
+/// markers would break.
+fn opens_block(block: &str) -> bool {
+    let ordered =
+        block.split_once(". ").is_some_and(|(number, _)| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()));
+    ordered || ["#", ">", "- ", "* ", "|", "```", "$$"].iter().any(|syntax| block.starts_with(syntax))
+}
+
+/// Read the bold, italic, strikethrough and color declarations of an inline `style`, ignoring everything else.
+fn span_style(style: &str) -> SpanStyle<'_> {
+    let mut out = SpanStyle::default();
+    for (property, value) in style.split(';').filter_map(|declaration| declaration.split_once(':')) {
+        let value = value.trim();
+        match property.trim().to_ascii_lowercase().as_str() {
+            "font-weight" => {
+                out.bold = matches!(value, "bold" | "bolder") || value.parse::<u16>().is_ok_and(|weight| weight >= 600)
+            }
+            "font-style" => out.italic = matches!(value, "italic" | "oblique"),
+            "text-decoration" | "text-decoration-line" => out.strike = value.contains("line-through"),
+            // the value is written into an attribute, anything that could close it is dropped
+            "color" if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "#(),.% ".contains(c)) => {
+                out.color = Some(value)
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// The thumbnail of a video link, for the hosts whose thumbnail url follows from the video id.
+///
+/// Zhihu and bilibili only expose their covers through an api, their links stay plain.
+fn video_thumbnail(href: &str) -> Option<String> {
+    let url = url::Url::parse(href).ok()?;
+    let id = match url.host_str()?.trim_start_matches("www.").trim_start_matches("m.") {
+        "youtube.com" if url.path() == "/watch" => url.query_pairs().find(|(k, _)| k == "v")?.1.into_owned(),
+        "youtube.com" => url.path().strip_prefix("/shorts/").or_else(|| url.path().strip_prefix("/embed/"))?.to_string(),
+        "youtu.be" => url.path().trim_start_matches('/').to_string(),
+        _ => return None,
+    };
+    match !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        true => Some(format!("https://img.youtube.com/vi/{id}/hqdefault.jpg")),
+        false => None,
+    }
+}
+
+/// The destination of a link without zhihu's redirect, the protocol-relative form and the `utm_*` tracking parameters.
+fn clean_href(href: &str) -> Cow<'_, str> {
+    let href = match href.strip_prefix("//") {
+        Some(rest) => Cow::Owned(format!("https://{rest}")),
+        None => unwrap_redirect(href),
+    };
+    let Ok(mut url) = url::Url::parse(&href)
+    else {
+        return href;
+    };
+    if !url.query_pairs().any(|(k, _)| k.starts_with("utm_")) {
+        return href;
+    }
+    let kept: Vec<(String, String)> =
+        url.query_pairs().filter(|(k, _)| !k.starts_with("utm_")).map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    match kept.is_empty() {
+        true => url.set_query(None),
+        false => {
+            url.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    Cow::Owned(url.into())
+}
+
+/// Zhihu routes external links through `https://link.zhihu.com/?target=<url>`, recover the real destination.
+fn unwrap_redirect(href: &str) -> Cow<'_, str> {
+    match url::Url::parse(href) {
+        Ok(url) if url.host_str() == Some("link.zhihu.com") => match url.query_pairs().find(|(k, _)| k == "target") {
+            Some((_, target)) => Cow::Owned(target.into_owned()),
+            None => Cow::Borrowed(href),
+        },
+        _ => Cow::Borrowed(href),
+    }
+}
+
+/// Escape the `<` that would open a tag and the `&` that would start an entity, so prose shows them literally.
+fn escape_html(text: &str) -> Cow<'_, str> {
+    if !text.contains(['<', '&']) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len() + 8);
+    for (i, c) in text.char_indices() {
+        let rest = &text[i + c.len_utf8()..];
+        let escape = match c {
+            '<' => rest.starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')),
+            '&' => {
+                let name = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_alphanumeric() || c == '#').len();
+                name > 0 && rest[name..].starts_with(';')
+            }
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    Cow::Owned(out)
+}
+
+/// Zhihu lazy loads images, the real url lives in `data-original`, `data-actualsrc` or `srcset` while `src` may be a placeholder.
+///
+/// Inside a `<picture>`, the largest candidate of the first `<source>` wins, skipping webp and avif unless `modern` is set.
+fn image_source(img: Node<'_>, modern: bool) -> Option<&str> {
+    let usable = |src: &&str| !src.is_empty() && !src.starts_with("data:");
+    let picture = img.parent().filter(|parent| parent.is_a("picture"));
+    let sources = picture.into_iter().flat_map(|picture| picture.children()).filter(|n| n.is_a("source"));
+    let source = sources
+        .filter_map(|source| Some((source.get_attribute("type"), largest_candidate(source.get_attribute("srcset"))?)))
+        .find(|(kind, url)| modern || !is_modern_format(kind, url))
+        .map(|(_, url)| url)
+        .filter(usable);
+    source.or_else(|| {
+        ["data-original", "data-actualsrc"]
+            .into_iter()
+            .map(|name| img.get_attribute(name))
+            .find(usable)
+            .or_else(|| largest_candidate(img.get_attribute("srcset")).filter(usable))
+            .or_else(|| Some(img.get_attribute("src")).filter(usable))
+    })
+}
+
+/// The animated gif behind the static thumbnail zhihu shows in a gif player, which shares the path of the thumbnail.
+fn animated_source(img: Node<'_>) -> Option<String> {
+    let player = img.get_attribute("class").split_whitespace().any(|class| class == "ztext-gif")
+        || img.parent().and_then(|parent| parent.as_data()).is_some_and(|parent| parent.has_class("GifPlayer"));
+    if !player {
+        return None;
+    }
+    let is_gif = |url: &&str| url.split(['?', '#']).next().is_some_and(|path| path.to_ascii_lowercase().ends_with(".gif"));
+    let sources = ["data-original", "data-actualsrc", "src"].map(|name| img.get_attribute(name));
+    if let Some(gif) = sources.into_iter().find(is_gif) {
+        return Some(gif.to_string());
+    }
+    let thumbnail = [img.get_attribute("data-thumbnail")].into_iter().chain(sources).find(|url| !url.is_empty())?;
+    let path = thumbnail.split(['?', '#']).next().unwrap_or(thumbnail);
+    let (stem, _) = path.rsplit_once('.').filter(|(stem, extension)| !stem.ends_with('/') && !extension.contains('/'))?;
+    Some(format!("{}.gif", stem))
+}
+
+/// Whether a `<source>` offers webp or avif, which older viewers cannot display.
+fn is_modern_format(kind: &str, url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_ascii_lowercase();
+    matches!(kind, "image/webp" | "image/avif") || path.ends_with(".webp") || path.ends_with(".avif")
+}
+
+/// The candidate of a `srcset` with the largest width or density descriptor, a missing descriptor counts as `1x`.
+fn largest_candidate(srcset: &str) -> Option<&str> {
+    srcset
+        .split(',')
+        .filter_map(|candidate| {
+            let mut parts = candidate.split_whitespace();
+            let url = parts.next()?;
+            let size = match parts.next() {
+                Some(descriptor) => descriptor.trim_end_matches(char::is_alphabetic).parse::<f64>().ok()?,
+                None => 1.0,
+            };
+            Some((url, size))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(url, _)| url)
+}
+
+/// The latex of an `<img eeimg>` formula, from `data-formula`, the `tex` query of the equation url or the alt text.
+fn eeimg_tex(img: Node<'_>) -> Option<String> {
+    let formula = img.get_attribute("data-formula").trim();
+    if !formula.is_empty() {
+        return Some(formula.to_string());
+    }
+    let from_src = img.get_attribute("src").split_once('?').and_then(|(_, query)| {
+        url::form_urlencoded::parse(query.as_bytes()).find(|(k, _)| k == "tex").map(|(_, tex)| tex.trim().to_string())
+    });
+    match from_src {
+        Some(tex) if !tex.is_empty() => Some(tex),
+        _ => match img.get_attribute("alt").trim() {
+            "" | "[公式]" => None,
+            alt => Some(alt.to_string()),
+        },
+    }
+}
+
+/// The text of a code block exactly as displayed: `<br>` are line breaks and non-breaking spaces are plain indentation.
+fn preformatted_text(pre: Node<'_>) -> String {
+    let mut code = String::new();
+    for node in pre.descendants() {
+        if let Some(text) = node.as_text() {
+            code.extend(text.chars().map(|c| if c == '\u{a0}' { ' ' } else { c }));
+        }
+        else if node.is_a("br") {
+            code.push('\n');
+        }
+    }
+    code
+}
+
+/// The language of a code block, from the `lang` of `<pre>` or the `language-*` class of its `<code>`.
+///
+/// Zhihu's names are turned into the info strings highlighters know, a declared plain text stays `text` and is
+/// never guessed.
+fn code_language(pre: Node<'_>) -> String {
+    let language = match pre.get_attribute("lang") {
+        "" => pre
+            .descendants()
+            .find(|n| n.is_a("code"))
+            .and_then(|code| code.get_attribute("class").split_whitespace().find_map(|c| c.strip_prefix("language-")))
+            .unwrap_or(""),
+        lang => lang,
+    };
+    match language.trim().to_ascii_lowercase().as_str() {
+        "plain" | "plaintext" | "none" => "text".to_string(),
+        "c++" => "cpp".to_string(),
+        "c#" => "csharp".to_string(),
+        "f#" => "fsharp".to_string(),
+        "objective-c" | "objectivec" => "objc".to_string(),
+        "golang" => "go".to_string(),
+        "shell" | "sh" | "console" => "bash".to_string(),
+        language => language.to_string(),
+    }
+}
+
+/// A code span fenced by one backtick more than the longest run inside it.
+///
+/// Code touching the fence with a backtick, or with spaces on both ends, is padded by a space the parser strips.
+fn code_span(code: &str) -> String {
+    let fence = "`".repeat(longest_backtick_run(code) + 1);
+    let padded = code.starts_with('`') || code.ends_with('`') || (code.starts_with(' ') && code.ends_with(' '));
+    match padded && !code.trim().is_empty() {
+        true => format!("{0} {1} {0}", fence, code),
+        false => format!("{0}{1}{0}", fence, code),
+    }
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// A formula is displayed when it is the only content of its paragraph, otherwise it is inline.
+fn is_display_math(node: Node) -> bool {
+    match node.parent() {
+        Some(parent) if parent.is_a("p") => parent
+            .children()
+            .all(|child| child == node || child.is_a("br") || child.as_text().is_some_and(|t| t.trim().is_empty())),
+        _ => false,
+    }
+}
@@ -0,0 +1,110 @@
+use htmler::{Node, NodeKind};
+
+/// Convert a `<math>` element to latex.
+///
+/// A latex annotation, which most MathML producers embed, is taken verbatim.
+/// Otherwise the presentation markup is translated, covering scripts, fractions, roots and common symbols,
+/// unknown elements contribute their children.
+pub(crate) fn mathml_to_tex(math: Node<'_>) -> String {
+    let annotation = math
+        .descendants()
+        .find(|n| n.is_a("annotation") && matches!(n.get_attribute("encoding"), "application/x-tex" | "TeX" | "LaTeX"));
+    match annotation {
+        Some(tex) => tex.inner_text().trim().to_string(),
+        None => convert(math).trim().to_string(),
+    }
+}
+
+fn convert(node: Node<'_>) -> String {
+    let arguments: Vec<String> = node.children().filter(|child| child.as_kind().is_element()).map(convert).collect();
+    let argument = |i: usize| arguments.get(i).map_or("", String::as_str);
+    match node.as_kind() {
+        NodeKind::Element(e) => match e.name() {
+            "mi" => identifier(node.inner_text().trim()),
+            "mn" | "mo" => node.inner_text().trim().chars().map(symbol).collect(),
+            "mtext" => format!("\\text{{{}}}", node.inner_text().trim()),
+            "mspace" => "\\ ".to_string(),
+            "mfrac" => format!("\\frac{{{}}}{{{}}}", argument(0), argument(1)),
+            "msqrt" => format!("\\sqrt{{{}}}", arguments.concat()),
+            "mroot" => format!("\\sqrt[{}]{{{}}}", argument(1), argument(0)),
+            "msup" => format!("{{{}}}^{{{}}}", argument(0), argument(1)),
+            "msub" => format!("{{{}}}_{{{}}}", argument(0), argument(1)),
+            "msubsup" => format!("{{{}}}_{{{}}}^{{{}}}", argument(0), argument(1), argument(2)),
+            "mover" => format!("\\overset{{{}}}{{{}}}", argument(1), argument(0)),
+            "munder" => format!("\\underset{{{}}}{{{}}}", argument(1), argument(0)),
+            "munderover" => format!("{{{}}}_{{{}}}^{{{}}}", argument(0), argument(1), argument(2)),
+            "mfenced" => {
+                let open = delimiter(e.get_attribute("open").unwrap_or("("));
+                let close = delimiter(e.get_attribute("close").unwrap_or(")"));
+                format!("\\left{}{}\\right{}", open, arguments.join(","), close)
+            }
+            "mtable" => format!("\\begin{{matrix}}{}\\end{{matrix}}", arguments.join("\\\\")),
+            "mtr" => arguments.join("&"),
+            // semantics, mrow, mstyle, mtd and anything unknown
+            _ => arguments.concat(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Functions latex sets upright with a command of their own.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh", "coth", "log", "ln", "lg",
+    "exp", "lim", "max", "min", "sup", "inf", "det", "gcd", "deg", "dim", "ker", "arg", "Pr",
+];
+
+/// An identifier: a function name such as `sin` becomes `\sin`, any other name of several letters is set upright
+/// as MathML shows it, single letters stay variables.
+fn identifier(name: &str) -> String {
+    match name.chars().count() {
+        0 | 1 => name.chars().map(symbol).collect(),
+        _ if FUNCTIONS.contains(&name) => format!("\\{name} "),
+        _ => format!("\\mathrm{{{name}}}"),
+    }
+}
+
+/// A fence of `<mfenced>` as a `\left` or `\right` delimiter, braces are escaped and a missing fence is `.`.
+fn delimiter(fence: &str) -> &str {
+    match fence.trim() {
+        "" => ".",
+        "{" => "\\{",
+        "}" => "\\}",
+        fence => fence,
+    }
+}
+
+/// The latex command of a symbol, or the symbol itself.
+fn symbol(c: char) -> String {
+    let command = match c {
+        '×' => "\\times ",
+        '÷' => "\\div ",
+        '±' => "\\pm ",
+        '·' | '⋅' => "\\cdot ",
+        '≤' => "\\leq ",
+        '≥' => "\\geq ",
+        '≠' => "\\neq ",
+        '≈' => "\\approx ",
+        '∞' => "\\infty ",
+        '∑' => "\\sum ",
+        '∏' => "\\prod ",
+        '∫' => "\\int ",
+        '∂' => "\\partial ",
+        '→' => "\\to ",
+        '∈' => "\\in ",
+        'α' => "\\alpha ",
+        'β' => "\\beta ",
+        'γ' => "\\gamma ",
+        'δ' => "\\delta ",
+        'ε' => "\\epsilon ",
+        'θ' => "\\theta ",
+        'λ' => "\\lambda ",
+        'μ' => "\\mu ",
+        'π' => "\\pi ",
+        'σ' => "\\sigma ",
+        'φ' => "\\phi ",
+        'ω' => "\\omega ",
+        '\u{2061}' | '\u{2062}' | '\u{2063}' => "",
+        c => return c.to_string(),
+    };
+    command.to_string()
+}
@@ -0,0 +1,691 @@
+use crate::{ZhihuError, ZhihuResult, utils::parse_count};
+use htmler::{Html, Node, Selector};
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    borrow::Cow,
+    fmt::{Debug, Formatter},
+    sync::{Arc, OnceLock},
+};
+
+mod markdown;
+mod mathml;
+
+#[cfg(feature = "network")]
+pub(crate) use self::markdown::{DiscussionAnswer, write_discussion};
+pub(crate) use self::markdown::{Document, MarkdownRenderer, Reference, render_section, write_document};
+
+/// Options that control how zhihu content is rendered to markdown.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::{RenderOptions, Typography};
+/// let options = RenderOptions::default().typography(Typography::Smart);
+/// ```
+#[derive(Clone, Default)]
+pub struct RenderOptions {
+    pub(crate) mode: ParseMode,
+    pub(crate) typography: Typography,
+    pub(crate) citation: bool,
+    pub(crate) frontmatter: bool,
+    pub(crate) related: bool,
+    pub(crate) references: bool,
+    pub(crate) footnotes: bool,
+    pub(crate) frontmatter_format: FrontmatterFormat,
+    pub(crate) post_process: Option<PostProcess>,
+    pub(crate) remove_selectors: Vec<Selector>,
+    pub(crate) numbered_alt: Option<String>,
+    pub(crate) raw_html: bool,
+    pub(crate) loose_lists: bool,
+    pub(crate) heading_offset: usize,
+    pub(crate) update_sections: bool,
+    pub(crate) guess_language: bool,
+    pub(crate) video_thumbnails: bool,
+    pub(crate) link_card_images: bool,
+    pub(crate) edit_note: bool,
+    pub(crate) provenance: bool,
+    pub(crate) copyright: CopyrightPolicy,
+    pub(crate) modern_images: bool,
+    pub(crate) caption_lines: bool,
+    pub(crate) raw_mathml: bool,
+    pub(crate) math_delimiters: MathDelimiters,
+    pub(crate) inline_styles: bool,
+    pub(crate) question_description: bool,
+    pub(crate) author_byline: bool,
+    pub(crate) engagement: bool,
+    pub(crate) trailing_boilerplate: Vec<Regex>,
+}
+
+/// A user supplied transformation of the final markdown.
+pub(crate) type PostProcess = Arc<dyn Fn(String) -> String + Send + Sync>;
+
+impl Debug for RenderOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderOptions")
+            .field("mode", &self.mode)
+            .field("typography", &self.typography)
+            .field("citation", &self.citation)
+            .field("frontmatter", &self.frontmatter)
+            .field("related", &self.related)
+            .field("references", &self.references)
+            .field("footnotes", &self.footnotes)
+            .field("frontmatter_format", &self.frontmatter_format)
+            .field("post_process", &self.post_process.is_some())
+            .field("remove_selectors", &self.remove_selectors)
+            .field("numbered_alt", &self.numbered_alt)
+            .field("raw_html", &self.raw_html)
+            .field("loose_lists", &self.loose_lists)
+            .field("heading_offset", &self.heading_offset)
+            .field("update_sections", &self.update_sections)
+            .field("guess_language", &self.guess_language)
+            .field("video_thumbnails", &self.video_thumbnails)
+            .field("link_card_images", &self.link_card_images)
+            .field("edit_note", &self.edit_note)
+            .field("provenance", &self.provenance)
+            .field("copyright", &self.copyright)
+            .field("modern_images", &self.modern_images)
+            .field("caption_lines", &self.caption_lines)
+            .field("raw_mathml", &self.raw_mathml)
+            .field("math_delimiters", &self.math_delimiters)
+            .field("inline_styles", &self.inline_styles)
+            .field("question_description", &self.question_description)
+            .field("author_byline", &self.author_byline)
+            .field("engagement", &self.engagement)
+            .field("trailing_boilerplate", &self.trailing_boilerplate)
+            .finish()
+    }
+}
+
+impl RenderOptions {
+    /// Report content that could not be found, which is only an error in [`ParseMode::Strict`].
+    pub(crate) fn missing_content(&self, selector: &str) -> ZhihuResult<()> {
+        match self.mode {
+            ParseMode::Lenient => Ok(()),
+            ParseMode::Strict => Err(ZhihuError::MissingContent(selector.to_string())),
+        }
+    }
+    /// Remove the nodes matching [`RenderOptions::remove_selectors`] before anything is extracted.
+    pub(crate) fn prune(&self, html: &mut Html) {
+        for selector in &self.remove_selectors {
+            html.remove_matches(selector);
+        }
+    }
+    /// Set how unexpected markup is handled.
+    pub fn mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    /// Set how answers whose author forbids reposting are treated.
+    pub fn copyright(mut self, copyright: CopyrightPolicy) -> Self {
+        self.copyright = copyright;
+        self
+    }
+    /// Set how quotes and dashes in prose are normalized.
+    pub fn typography(mut self, typography: Typography) -> Self {
+        self.typography = typography;
+        self
+    }
+    /// Emit a frontmatter block with the title, the estimated reading time, and the author, url, topics and dates the
+    /// page has.
+    pub fn frontmatter(mut self, frontmatter: bool) -> Self {
+        self.frontmatter = frontmatter;
+        self
+    }
+    /// Open the document with an html comment naming the source url, the fetch time and the generator version.
+    ///
+    /// The comment follows the frontmatter, which has to stay on the first line.
+    pub fn provenance(mut self, provenance: bool) -> Self {
+        self.provenance = provenance;
+        self
+    }
+    /// Set the syntax of the frontmatter block, YAML unless told otherwise.
+    pub fn frontmatter_format(mut self, format: FrontmatterFormat) -> Self {
+        self.frontmatter_format = format;
+        self
+    }
+    /// Write `<` and `&` of prose as they are, letting markdown read them as html, instead of escaping them.
+    ///
+    /// Code is always written verbatim, entities are decoded exactly once by the html parser.
+    pub fn raw_html(mut self, raw_html: bool) -> Self {
+        self.raw_html = raw_html;
+        self
+    }
+    /// Let the `<source>` of a `<picture>` be webp or avif, which is smaller but not displayed everywhere.
+    ///
+    /// Off by default, so the first jpeg or png source, or else the fallback `<img>`, is linked.
+    pub fn modern_images(mut self, modern_images: bool) -> Self {
+        self.modern_images = modern_images;
+        self
+    }
+    /// Write the `<figcaption>` of an image as an italic line beneath it, rather than as its alt text or title.
+    pub fn caption_lines(mut self, caption_lines: bool) -> Self {
+        self.caption_lines = caption_lines;
+        self
+    }
+    /// Render links to youtube videos as their thumbnail, linking to the video.
+    ///
+    /// Links to hosts whose thumbnail cannot be derived from the url stay plain links.
+    pub fn video_thumbnails(mut self, video_thumbnails: bool) -> Self {
+        self.video_thumbnails = video_thumbnails;
+        self
+    }
+    /// Render link cards as their thumbnail linking to the target, titled by the card, when they have one.
+    ///
+    /// Off by default, cards are then plain `[title](url)` links.
+    pub fn link_card_images(mut self, link_card_images: bool) -> Self {
+        self.link_card_images = link_card_images;
+        self
+    }
+    /// Label code blocks that do not declare a language with the one [`crate::utils::guess_language`] recognizes.
+    ///
+    /// Off by default, a wrong label highlights worse than none.
+    pub fn guess_language(mut self, guess_language: bool) -> Self {
+        self.guess_language = guess_language;
+        self
+    }
+    /// Separate the paragraphs of a list item by blank lines, making a loose list, instead of continuation lines.
+    ///
+    /// The first paragraph always stays on the line of the item marker.
+    pub fn loose_lists(mut self, loose_lists: bool) -> Self {
+        self.loose_lists = loose_lists;
+        self
+    }
+    /// Demote the headings of the content by `offset` levels, so that an `<h2>` becomes `###` with an offset of 1.
+    ///
+    /// The title already is the only `#` heading, headings deeper than `######` stay at that level.
+    pub fn heading_offset(mut self, offset: usize) -> Self {
+        self.heading_offset = offset;
+        self
+    }
+    /// Open an update section with a `---` rule and a `####` heading at a short standalone paragraph such as
+    /// `补充说明：` or `更新 2023.5.1`.
+    ///
+    /// Off by default, such a paragraph is only told apart by its text, so ordinary prose could be taken for one.
+    pub fn update_sections(mut self, update_sections: bool) -> Self {
+        self.update_sections = update_sections;
+        self
+    }
+    /// Keep MathML formulas as `<math>` html instead of converting them to latex.
+    pub fn raw_mathml(mut self, raw_mathml: bool) -> Self {
+        self.raw_mathml = raw_mathml;
+        self
+    }
+    /// Set how formulas are delimited, `$` and `$$` unless told otherwise.
+    pub fn math_delimiters(mut self, delimiters: MathDelimiters) -> Self {
+        self.math_delimiters = delimiters;
+        self
+    }
+    /// Read the inline `style` of spans, writing bold and italic as markdown emphasis and colors as html spans.
+    ///
+    /// Off by default, styled spans are then written as plain text.
+    pub fn inline_styles(mut self, inline_styles: bool) -> Self {
+        self.inline_styles = inline_styles;
+        self
+    }
+    /// Give images without alt text nor caption a numbered alt, `prefix` followed by the index of the figure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zhihu_link::RenderOptions;
+    /// // ![图1](...), ![图2](...)
+    /// let options = RenderOptions::default().numbered_alt("图");
+    /// ```
+    pub fn numbered_alt(mut self, prefix: impl Into<String>) -> Self {
+        self.numbered_alt = Some(prefix.into());
+        self
+    }
+    /// List the references cited in the text under a `## 参考` heading, with their titles and links.
+    pub fn references(mut self, references: bool) -> Self {
+        self.references = references;
+        self
+    }
+    /// Cite references as markdown footnotes, `[^1]` in the text and their definitions at the end, instead of the
+    /// `[1]` and the `## 参考` list of [`Self::references`].
+    pub fn footnotes(mut self, footnotes: bool) -> Self {
+        self.footnotes = footnotes;
+        self
+    }
+    /// Introduce the author under the title with a link to their profile and their bio.
+    ///
+    /// Without it neither answers nor articles have a byline, only a deleted answer says so under its title.
+    pub fn author_byline(mut self, author_byline: bool) -> Self {
+        self.author_byline = author_byline;
+        self
+    }
+    /// Write the vote, comment and favorite counts the page shows into the frontmatter, as `votes`, `comment_count`
+    /// and `favorites`.
+    pub fn engagement(mut self, engagement: bool) -> Self {
+        self.engagement = engagement;
+        self
+    }
+    /// Quote the description of the question under the title of an answer.
+    pub fn question_description(mut self, question_description: bool) -> Self {
+        self.question_description = question_description;
+        self
+    }
+    /// End an edited answer with an italic `*编辑于 <date>*` line.
+    pub fn edit_note(mut self, edit_note: bool) -> Self {
+        self.edit_note = edit_note;
+        self
+    }
+    /// Append the related questions and column recommendations of the page as an appendix.
+    pub fn related(mut self, related: bool) -> Self {
+        self.related = related;
+        self
+    }
+    /// Append a footer that links back to the original page.
+    pub fn citation(mut self, citation: bool) -> Self {
+        self.citation = citation;
+        self
+    }
+    /// Delete the nodes matching any of the selectors, such as ads or author bios, before rendering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zhihu_link::{RenderOptions, Selector};
+    /// let options =
+    ///     RenderOptions::default().remove_selectors(vec![Selector::new(".RichContent-actions")]);
+    /// ```
+    pub fn remove_selectors(mut self, selectors: Vec<Selector>) -> Self {
+        self.remove_selectors = selectors;
+        self
+    }
+    /// Drop the last lines of the content while one of the patterns matches them, such as "欢迎关注" or "点个赞" pleas.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zhihu_link::{Regex, RenderOptions};
+    /// let options = RenderOptions::default()
+    ///     .trailing_boilerplate(vec![Regex::new("^(欢迎|求)(关注|点赞)").unwrap()]);
+    /// ```
+    pub fn trailing_boilerplate(mut self, patterns: Vec<Regex>) -> Self {
+        self.trailing_boilerplate = patterns;
+        self
+    }
+    /// Transform the final markdown, after the built-in normalization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zhihu_link::RenderOptions;
+    /// let options = RenderOptions::default().post_process(|md| md.replace("知乎", "Zhihu"));
+    /// ```
+    pub fn post_process<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(String) -> String + Send + Sync + 'static,
+    {
+        self.post_process = Some(Arc::new(hook));
+        self
+    }
+}
+
+/// A link to related content zhihu recommends next to an answer or an article.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedLink {
+    /// The title of the linked content.
+    pub title: String,
+    /// The absolute url of the linked content.
+    pub url: String,
+}
+
+/// The author of an answer or an article, as the page introduces them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthorInfo {
+    /// The display name.
+    pub name: String,
+    /// The one line introduction under the name.
+    pub bio: Option<String>,
+    /// The absolute url of the avatar image.
+    pub avatar: Option<String>,
+    /// The absolute url of the profile page.
+    pub url: Option<String>,
+}
+
+impl AuthorInfo {
+    /// `作者: [name](url) · bio`, the parts the page has.
+    pub(crate) fn byline(&self) -> String {
+        let mut out = match &self.url {
+            Some(url) => format!("作者: [{}]({})", self.name, url),
+            None => format!("作者: {}", self.name),
+        };
+        if let Some(bio) = &self.bio {
+            out.push_str(" · ");
+            out.push_str(bio);
+        }
+        out
+    }
+}
+
+/// Read the first author card of a page, `None` when it has no name.
+pub(crate) fn author_info(html: &Html) -> Option<AuthorInfo> {
+    let card = html.select(&Selector::new("div.AuthorInfo")).next()?;
+    let base = url::Url::parse("https://www.zhihu.com").expect("valid base url");
+    let first = |selector: &str, attribute: Option<&str>| {
+        card.select(&Selector::new(selector))
+            .map(|node| match attribute {
+                Some(attribute) => node.get_attribute(attribute).trim().to_string(),
+                None => node.inner_text().trim().to_string(),
+            })
+            .find(|value| !value.is_empty())
+    };
+    let absolute = |url: String| base.join(&url).map_or(url, String::from);
+    let name = first(r#"meta[itemprop="name"]"#, Some("content")).or_else(|| first(".AuthorInfo-name", None))?;
+    Some(AuthorInfo {
+        name,
+        bio: first(".AuthorInfo-badgeText", None),
+        avatar: first(r#"meta[itemprop="image"]"#, Some("content"))
+            .or_else(|| first("img.AuthorInfo-avatar", Some("src")))
+            .map(absolute),
+        url: first(r#"meta[itemprop="url"]"#, Some("content")).or_else(|| first("a.UserLink-link", Some("href"))).map(absolute),
+    })
+}
+
+/// How readers engaged with an answer or an article, as far as the page tells.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Engagement {
+    /// The number of up votes.
+    pub votes: Option<usize>,
+    /// The number of comments.
+    pub comments: Option<usize>,
+    /// The number of collections the content was added to.
+    pub favorites: Option<usize>,
+    /// The number of shares.
+    pub shares: Option<usize>,
+}
+
+impl Engagement {
+    /// Read the counts from the action buttons of the content `item`, the ones they lack from the embedded `entity`.
+    pub(crate) fn extract(item: Node, entity: Option<&Value>) -> Self {
+        let mut out = Self::default();
+        for node in item.select(&Selector::new(".ContentItem-actions button")) {
+            let text = node.inner_text();
+            let count = match text {
+                _ if text.contains("赞同") => &mut out.votes,
+                _ if text.contains("评论") => &mut out.comments,
+                _ if text.contains("收藏") => &mut out.favorites,
+                _ if text.contains("分享") => &mut out.shares,
+                _ => continue,
+            };
+            *count = count.or(parse_count(&text));
+        }
+        if let Some(entity) = entity {
+            let field = |key: &str| entity[key].as_u64().map(|count| count as usize);
+            out.votes = out.votes.or(field("voteupCount"));
+            out.comments = out.comments.or(field("commentCount"));
+            out.favorites = out.favorites.or(field("favlistsCount"));
+        }
+        out
+    }
+}
+
+/// The `.ContentItem` of the answer or article with `id`, or else the one around the first `content`, so that
+/// what the page shows of other content is left out; the whole page when there is neither.
+pub(crate) fn content_item<'a>(html: &'a Html, content: &str, id: Option<usize>) -> Node<'a> {
+    let is_item = |node: &Node| node.as_data().is_some_and(|e| e.has_class("ContentItem") || e.has_class("Post-Main"));
+    let by_id = id.and_then(|id| {
+        let id = id.to_string();
+        html.select(&Selector::new(".ContentItem")).find(|item| item.get_attribute("name") == id)
+    });
+    let around = || {
+        let content = html.select(&Selector::new(content)).next()?;
+        std::iter::successors(content.parent(), Node::parent).find(is_item)
+    };
+    by_id.or_else(around).unwrap_or_else(|| html.root_node())
+}
+
+/// The entity of the `js-initialData` state of a page, `kind` being `answers` or `articles`.
+///
+/// The entity with the given id, or else the first one, `None` when the page has no state to parse.
+pub(crate) fn initial_entity(html: &Html, kind: &str, id: Option<usize>) -> Option<Value> {
+    let script = html.select(&Selector::new("script#js-initialData")).next()?;
+    let mut data = serde_json::from_str::<Value>(&script.inner_text()).ok()?;
+    let entities = data.pointer_mut(&format!("/initialState/entities/{}", kind))?.as_object_mut()?;
+    let key = id.map(|id| id.to_string()).filter(|id| entities.contains_key(id));
+    match key {
+        Some(key) => entities.remove(&key),
+        None => entities.values_mut().next().map(Value::take),
+    }
+}
+
+/// When the content was published and last edited, as the `发布于` and `编辑于` line of the page shows it,
+/// or else as the `dateCreated`, `datePublished` and `dateModified` meta tags tell.
+///
+/// Content whose modification time is its creation time was never edited.
+pub(crate) fn content_times(html: &Html) -> (Option<String>, Option<String>) {
+    let line = Selector::new(".ContentItem-time");
+    let after = |marker: &str| {
+        html.select(&line).find_map(|node| {
+            let text = node.inner_text();
+            let date = text.split_once(marker)?.1.trim();
+            (!date.is_empty()).then(|| date.to_string())
+        })
+    };
+    let meta = |names: &str| {
+        html.select(&Selector::new(names))
+            .map(|node| node.get_attribute("content").trim().to_string())
+            .find(|date| !date.is_empty())
+    };
+    let created = meta(r#"meta[itemprop="dateCreated"], meta[itemprop="datePublished"]"#);
+    let modified = meta(r#"meta[itemprop="dateModified"]"#).filter(|modified| created.as_ref() != Some(modified));
+    (after("发布于").or(created), after("编辑于").or(modified))
+}
+
+/// The topics a question or an article is filed under, in page order and without duplicates.
+pub(crate) fn topics(html: &Html) -> Vec<String> {
+    let selector = Selector::new(".QuestionHeader-topics .Tag, .Post-topicsAndReviewer .Tag");
+    let mut topics: Vec<String> = vec![];
+    for node in html.select(&selector) {
+        let topic = node.inner_text().trim().to_string();
+        if !topic.is_empty() && !topics.contains(&topic) {
+            topics.push(topic);
+        }
+    }
+    topics
+}
+
+/// Collect the related questions and column recommendations of a page, without duplicates.
+pub(crate) fn related_links(html: &Html) -> Vec<RelatedLink> {
+    let selector = Selector::new(".SimilarQuestions-item a[href], .Recommendations-Main a[href]");
+    let base = url::Url::parse("https://www.zhihu.com").expect("valid base url");
+    let mut links: Vec<RelatedLink> = vec![];
+    for node in html.select(&selector) {
+        let title = node.inner_text().trim().to_string();
+        let Ok(url) = base.join(node.get_attribute("href"))
+        else {
+            continue;
+        };
+        if !title.is_empty() && !links.iter().any(|link| link.url == url.as_str()) {
+            links.push(RelatedLink { title, url: url.into() });
+        }
+    }
+    links
+}
+
+/// The title announced in the head of a page, `og:title` or else `<title>` without the ` - 知乎` suffix.
+pub(crate) fn page_title(html: &Html) -> Option<String> {
+    let og = Selector::new(r#"meta[property="og:title"]"#);
+    let title = Selector::new("head title");
+    let candidates = html.select(&og).map(|node| node.get_attribute("content").to_string());
+    candidates
+        .chain(html.select(&title).map(|node| node.inner_text()))
+        .map(|title| title.trim().trim_end_matches("- 知乎").trim_end().to_string())
+        .find(|title| !title.is_empty())
+}
+
+/// Quote a string so that it is valid in YAML and TOML alike.
+pub(crate) fn quote_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// How the converter reacts to markup it does not understand.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Unknown elements are rendered through their children and missing content is skipped.
+    #[default]
+    Lenient,
+    /// Unknown elements and missing content are reported as errors, useful to catch changes in zhihu's markup.
+    Strict,
+}
+
+/// What to do with answers whose author forbids reposting.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum CopyrightPolicy {
+    /// Render and save them like any other answer.
+    #[default]
+    Ignore,
+    /// Put a copyright notice under the title.
+    Notice,
+    /// Put the notice under the title and refuse to save them with [`ZhihuError::RepostForbidden`].
+    Refuse,
+}
+
+/// The syntax of the frontmatter block.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum FrontmatterFormat {
+    /// `---` delimited YAML, understood by most static site generators.
+    #[default]
+    Yaml,
+    /// `+++` delimited TOML, preferred by Zola and Hugo.
+    Toml,
+}
+
+impl FrontmatterFormat {
+    fn delimiter(&self) -> &'static str {
+        match self {
+            FrontmatterFormat::Yaml => "---",
+            FrontmatterFormat::Toml => "+++",
+        }
+    }
+    /// Write a `key` with a date, a native datetime in TOML when the date is written in RFC 3339, a string otherwise.
+    ///
+    /// Zhihu shows dates like `2023-05-06 07:08`, which lack the seconds TOML requires and stay strings.
+    fn write_date<W: std::fmt::Write>(&self, f: &mut W, key: &str, date: &str) -> std::fmt::Result {
+        static RFC3339: OnceLock<Regex> = OnceLock::new();
+        let rfc3339 = RFC3339.get_or_init(|| {
+            Regex::new(r"^\d{4}-\d{2}-\d{2}([T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?)?$").expect("valid pattern")
+        });
+        match self {
+            FrontmatterFormat::Toml if rfc3339.is_match(date) => self.write_field(f, key, &date),
+            _ => self.write_field(f, key, &quote_string(date)),
+        }
+    }
+    /// Write a `key` with an already serialized `value`.
+    fn write_field<W: std::fmt::Write>(&self, f: &mut W, key: &str, value: &dyn std::fmt::Display) -> std::fmt::Result {
+        match self {
+            FrontmatterFormat::Yaml => writeln!(f, "{}: {}", key, value),
+            FrontmatterFormat::Toml => writeln!(f, "{} = {}", key, value),
+        }
+    }
+}
+
+/// The delimiters of inline and display formulas.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum MathDelimiters {
+    /// `$...$` inline and `$$` on their own lines for display, understood by most markdown renderers.
+    #[default]
+    Dollars,
+    /// `\(...\)` inline and `\[` `\]` for display, for renderers where a `$` is just a dollar sign.
+    Brackets,
+}
+
+impl MathDelimiters {
+    /// The opening and closing delimiters of inline and of display formulas.
+    fn pair(&self) -> ((&'static str, &'static str), (&'static str, &'static str)) {
+        match self {
+            MathDelimiters::Dollars => (("$", "$"), ("$$", "$$")),
+            MathDelimiters::Brackets => (("\\(", "\\)"), ("\\[", "\\]")),
+        }
+    }
+}
+
+/// Normalization of quotes and dashes in prose text.
+///
+/// Only text nodes are affected, formulas and attribute values are kept verbatim.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Typography {
+    /// Keep quotes and dashes as they are.
+    #[default]
+    Preserve,
+    /// `"` and `'` become curly quotes, `--` becomes `–` and `---` becomes `—`.
+    Smart,
+    /// Curly quotes become `"` and `'`, `–` becomes `--` and `—` becomes `---`.
+    Straight,
+}
+
+impl Typography {
+    /// Apply the normalization to a piece of text.
+    ///
+    /// `previous` is the last character written before this text, used to decide whether a quote opens or closes.
+    pub fn apply<'a>(&self, text: &'a str, previous: Option<char>) -> Cow<'a, str> {
+        match self {
+            Typography::Preserve => Cow::Borrowed(text),
+            Typography::Smart => Cow::Owned(smarten(text, previous)),
+            Typography::Straight => Cow::Owned(straighten(text)),
+        }
+    }
+}
+
+fn smarten(text: &str, mut previous: Option<char>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '"' if quote_opens(previous) => '“',
+            '"' => '”',
+            '\'' if quote_opens(previous) => '‘',
+            '\'' => '’',
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                if chars.peek() == Some(&'-') {
+                    chars.next();
+                    '—'
+                }
+                else {
+                    '–'
+                }
+            }
+            c => c,
+        };
+        out.push(c);
+        previous = Some(c);
+    }
+    out
+}
+
+fn quote_opens(previous: Option<char>) -> bool {
+    match previous {
+        None => true,
+        Some(c) => c.is_whitespace() || matches!(c, '(' | '[' | '{' | '“' | '‘' | '–' | '—'),
+    }
+}
+
+fn straighten(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '“' | '”' | '„' => out.push('"'),
+            '‘' | '’' | '‚' => out.push('\''),
+            '–' => out.push_str("--"),
+            '—' => out.push_str("---"),
+            c => out.push(c),
+        }
+    }
+    out
+}
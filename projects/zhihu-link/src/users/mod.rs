@@ -0,0 +1,118 @@
+use crate::{
+    ContentKind, RenderOptions, ZhihuClient, ZhihuResult,
+    client::paginate,
+    exporter::{Exporter, content_id},
+    utils::{json_id, sanitize_file_name},
+};
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// How many items of a user are listed per request.
+const PAGE_SIZE: usize = 20;
+
+/// The content a zhihu user published, archived into a directory of their own.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use zhihu_link::ZhihuUser;
+/// # async fn run() -> zhihu_link::ZhihuResult<()> {
+/// // writes `backup/aster/answers/<answer id>.md`
+/// let reports = ZhihuUser::new("aster").export_answers("backup").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZhihuUser {
+    token: String,
+    export: Exporter,
+}
+
+impl ZhihuUser {
+    /// 通过用户的 url token, 即个人主页链接 `https://www.zhihu.com/people/<token>` 的最后一段创建, 使用默认的客户端
+    pub fn new(token: &str) -> Self {
+        Self::with_client(ZhihuClient::default(), token)
+    }
+    /// 通过用户的 url token 创建, 使用给定的客户端
+    pub fn with_client(client: ZhihuClient, token: &str) -> Self {
+        Self { token: token.to_string(), export: Exporter::new(client) }
+    }
+    /// 渲染回答和文章的选项
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.export.options = options;
+        self
+    }
+    /// 同时获取的回答或文章数, 至少为 1, 默认为 4
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.export.concurrency = concurrency.max(1);
+        self
+    }
+    /// 相邻两次获取回答或文章之间的最短间隔, 用于避免被限流, 默认不等待
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.export.interval = interval;
+        self
+    }
+    /// 用户的 url token
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+    /// 逐页列出用户的全部回答
+    pub async fn answers(&self) -> ZhihuResult<Vec<ContentKind>> {
+        let items = self.list("answers").await?;
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                Some(ContentKind::Answer { question: json_id(&item["question"]["id"])?, answer: json_id(&item["id"])? })
+            })
+            .collect())
+    }
+    /// 逐页列出用户发表的全部专栏文章
+    pub async fn articles(&self) -> ZhihuResult<Vec<ContentKind>> {
+        let items = self.list("articles").await?;
+        Ok(items.iter().filter_map(|item| Some(ContentKind::Article { id: json_id(&item["id"])? })).collect())
+    }
+    /// 把用户的全部回答保存到 `dir/<token>/answers` 下, 文件名为 `回答ID.md`, token 中不能作文件名的字符替换为 `_`
+    ///
+    /// 返回每个回答写入的文件或失败的原因, 按列出的顺序, 只有列出回答或创建目录失败时中止
+    pub async fn export_answers<P>(&self, dir: P) -> ZhihuResult<Vec<ZhihuResult<PathBuf>>>
+    where
+        P: AsRef<Path>,
+    {
+        let answers = self.answers().await?;
+        self.export(answers, &self.directory(dir.as_ref(), "answers")).await
+    }
+    /// 把用户的全部专栏文章保存到 `dir/<token>/articles` 下, 文件名为 `文章ID.md`, token 中不能作文件名的字符替换为 `_`
+    ///
+    /// 返回每篇文章写入的文件或失败的原因, 按列出的顺序, 只有列出文章或创建目录失败时中止
+    pub async fn export_articles<P>(&self, dir: P) -> ZhihuResult<Vec<ZhihuResult<PathBuf>>>
+    where
+        P: AsRef<Path>,
+    {
+        let articles = self.articles().await?;
+        self.export(articles, &self.directory(dir.as_ref(), "articles")).await
+    }
+    /// The directory of a kind of content under `dir`, the token is sanitized so that it stays one component.
+    fn directory(&self, dir: &Path, kind: &str) -> PathBuf {
+        dir.join(sanitize_file_name(&self.token)).join(kind)
+    }
+    /// Save every answer or article into `dir`, named after its id.
+    async fn export(&self, items: Vec<ContentKind>, dir: &Path) -> ZhihuResult<Vec<ZhihuResult<PathBuf>>> {
+        std::fs::create_dir_all(dir)?;
+        let reports = self
+            .export
+            .save_all(&items, dir, |index, item| match content_id(item) {
+                Some(id) => format!("{id}.md"),
+                None => format!("{}.md", index + 1),
+            })
+            .await;
+        Ok(reports.into_iter().map(|report| report.map(|(_, path)| path)).collect())
+    }
+    /// Every item of a list of the user, such as `answers`, page by page.
+    async fn list(&self, kind: &str) -> ZhihuResult<Vec<Value>> {
+        let client = &self.export.client;
+        paginate(client, &client.api_url(&format!("/members/{}/{}", self.token, kind)), PAGE_SIZE).await
+    }
+}
@@ -0,0 +1,469 @@
+//! Helper functions shared by the extractors, also useful when post-processing the output.
+
+use crate::{ZhihuError, ZhihuResult};
+use encoding_rs::{DecoderResult, Encoding, UTF_8};
+
+/// The canonical url of an answer.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::answer_url;
+/// assert_eq!(answer_url(58151047, 1), "https://www.zhihu.com/question/58151047/answer/1");
+/// ```
+pub fn answer_url(question: usize, answer: usize) -> String {
+    format!("https://www.zhihu.com/question/{question}/answer/{answer}")
+}
+
+/// The canonical url of a column article.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::article_url;
+/// assert_eq!(article_url(620388361), "https://zhuanlan.zhihu.com/p/620388361");
+/// ```
+pub fn article_url(id: usize) -> String {
+    format!("https://zhuanlan.zhihu.com/p/{id}")
+}
+
+/// The canonical url of a pin (想法).
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::pin_url;
+/// assert_eq!(pin_url(1630000000000000000), "https://www.zhihu.com/pin/1630000000000000000");
+/// ```
+pub fn pin_url(id: usize) -> String {
+    format!("https://www.zhihu.com/pin/{id}")
+}
+
+/// The canonical url of a question.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::question_url;
+/// assert_eq!(question_url(58151047), "https://www.zhihu.com/question/58151047");
+/// ```
+pub fn question_url(id: usize) -> String {
+    format!("https://www.zhihu.com/question/{id}")
+}
+
+/// Estimate how many minutes it takes to read the text.
+///
+/// CJK characters are read at about 400 per minute and other words at about 200 per minute,
+/// any non-empty text takes at least one minute.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::reading_minutes;
+/// assert_eq!(reading_minutes(""), 0);
+/// assert_eq!(reading_minutes("知乎"), 1);
+/// assert_eq!(reading_minutes(&"字".repeat(1000)), 3);
+/// ```
+pub fn reading_minutes(text: &str) -> usize {
+    let mut cjk = 0usize;
+    let mut words = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            cjk += 1;
+            in_word = false;
+        }
+        else if c.is_alphanumeric() {
+            if !in_word {
+                words += 1;
+            }
+            in_word = true;
+        }
+        else {
+            in_word = false;
+        }
+    }
+    if cjk == 0 && words == 0 {
+        return 0;
+    }
+    // minutes = cjk / 400 + words / 200, rounded up
+    (cjk + 2 * words).div_ceil(400).max(1)
+}
+
+/// Parse a counter as zhihu displays it, such as `1,024 收藏` or `分享 1.2 万`.
+///
+/// Returns `None` when the text carries no number.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::parse_count;
+/// assert_eq!(parse_count("1,024 收藏"), Some(1024));
+/// assert_eq!(parse_count("分享 1.2 万"), Some(12000));
+/// assert_eq!(parse_count("收藏"), None);
+/// ```
+pub fn parse_count(text: &str) -> Option<usize> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let rest = &text[start..];
+    let end = rest.find(|c: char| !c.is_ascii_digit() && c != ',' && c != '.').unwrap_or(rest.len());
+    let number: f64 = rest[..end].replace(',', "").parse().ok()?;
+    let scale = match rest[end..].trim_start().chars().next() {
+        Some('万') => 10_000.0,
+        Some('亿') => 100_000_000.0,
+        _ => 1.0,
+    };
+    Some((number * scale).round() as usize)
+}
+
+/// An id the api writes either as a number or as a string.
+pub(crate) fn json_id(value: &serde_json::Value) -> Option<usize> {
+    value.as_u64().or_else(|| value.as_str()?.parse().ok()).map(|id| id as usize)
+}
+
+/// Decode a page in the charset named by the `Content-Type` header or the `<meta>` of the page, UTF-8 by default.
+///
+/// Malformed input is an [`ZhihuError::Encoding`] error instead of being replaced silently.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::{utils::decode_html, ZhihuError};
+/// let gbk = b"<meta charset=\"gbk\"><p>\xd6\xaa\xba\xf5</p>";
+/// assert_eq!(decode_html(gbk, None).unwrap(), "<meta charset=\"gbk\"><p>知乎</p>");
+/// let error = decode_html(b"\xd6\xaa\xff", Some("text/html; charset=GBK")).unwrap_err();
+/// assert_eq!(error, ZhihuError::Encoding { charset: "GBK".to_string(), offset: 2 });
+/// ```
+pub fn decode_html(bytes: &[u8], content_type: Option<&str>) -> ZhihuResult<String> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let label = content_type.and_then(charset_of).or_else(|| charset_of(&head));
+    let encoding = label.and_then(|label| Encoding::for_label(label.as_bytes())).unwrap_or(UTF_8);
+    let mut decoder = encoding.new_decoder_with_bom_removal();
+    let capacity = decoder.max_utf8_buffer_length_without_replacement(bytes.len()).unwrap_or(bytes.len() * 3);
+    let mut text = String::with_capacity(capacity);
+    match decoder.decode_to_string_without_replacement(bytes, &mut text, true) {
+        (DecoderResult::InputEmpty, _) => Ok(text),
+        (DecoderResult::Malformed(malformed, consumed), read) => Err(ZhihuError::Encoding {
+            charset: encoding.name().to_string(),
+            offset: read - consumed as usize - malformed as usize,
+        }),
+        (DecoderResult::OutputFull, _) => unreachable!("the buffer fits the whole output"),
+    }
+}
+
+/// The value of the first `charset=` in `text`, unquoted.
+fn charset_of(text: &str) -> Option<&str> {
+    let start = text.to_ascii_lowercase().find("charset=")? + "charset=".len();
+    let value = text[start..].trim_start_matches(['"', '\'', ' ']);
+    let end = value.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_')).unwrap_or(value.len());
+    Some(&value[..end]).filter(|label| !label.is_empty())
+}
+
+/// Make `name` safe as a file name on every platform: path separators, reserved and control characters become `_`.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::sanitize_file_name;
+/// assert_eq!(sanitize_file_name("a/b: c?"), "a_b_ c_");
+/// assert_eq!(sanitize_file_name(" .. "), "_");
+/// ```
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+        .collect();
+    // windows drops trailing dots and spaces, a leading dot hides the file
+    let trimmed = cleaned.trim_matches(|c: char| c == '.' || c.is_whitespace());
+    match trimmed.is_empty() {
+        true => "_".to_string(),
+        false => trimmed.to_string(),
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF // hiragana and katakana
+        | 0x3400..=0x4DBF // cjk extension a
+        | 0x4E00..=0x9FFF // cjk unified ideographs
+        | 0xAC00..=0xD7AF // hangul syllables
+        | 0xF900..=0xFAFF // cjk compatibility ideographs
+        | 0x20000..=0x2FA1F // cjk extension b and later
+    )
+}
+
+/// Strip the `@<width>w.webp` style transform bilibili's CDN appends to image urls, yielding the original image.
+///
+/// Urls from other hosts are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::normalize_bilibili_image;
+/// let url = "https://i0.hdslb.com/bfs/article/8f4c.jpg@720w_1e_1c.webp";
+/// assert_eq!(normalize_bilibili_image(url), "https://i0.hdslb.com/bfs/article/8f4c.jpg");
+/// ```
+pub fn normalize_bilibili_image(url: &str) -> &str {
+    let host_start = url.find("//").map_or(0, |i| i + 2);
+    let path_start = url[host_start..].find('/').map_or(url.len(), |i| host_start + i);
+    let host = &url[host_start..path_start];
+    if host != "hdslb.com" && !host.ends_with(".hdslb.com") {
+        return url;
+    }
+    let name_start = url.rfind('/').map_or(0, |i| i + 1).max(path_start);
+    match url[name_start..].find('@') {
+        Some(at) => &url[..name_start + at],
+        None => url,
+    }
+}
+
+/// Strip the markdown syntax from `markdown`, keeping the text a reader would see, for example to index it for search.
+///
+/// Frontmatter, fences and rules are dropped, links and images keep their text, code keeps its content verbatim.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::strip_markdown;
+/// let markdown = "# 标题\n\n**粗体**与[链接](https://example.com), 调用 `a_b*c`\n\n- 列表";
+/// assert_eq!(strip_markdown(markdown), "标题\n\n粗体与链接, 调用 a_b*c\n\n列表");
+/// ```
+pub fn strip_markdown(markdown: &str) -> String {
+    let mut lines: Vec<String> = vec![];
+    let mut fence: Option<&str> = None;
+    let mut source = markdown.lines().peekable();
+    // frontmatter is metadata, not text
+    if let Some(delimiter) = source.peek().copied().filter(|line| matches!(*line, "---" | "+++")) {
+        source.next();
+        for line in source.by_ref() {
+            if line == delimiter {
+                break;
+            }
+        }
+    }
+    for line in source {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) && trimmed.trim_start_matches(['`', '~']).trim().is_empty() {
+                fence = None;
+            }
+            else {
+                lines.push(line.to_string());
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let marker = trimmed.chars().next().unwrap_or('`');
+            let length = trimmed.len() - trimmed.trim_start_matches(marker).len();
+            fence = Some(&trimmed[..length]);
+            continue;
+        }
+        if trimmed == "$$" || is_rule(trimmed) {
+            continue;
+        }
+        lines.push(strip_inline(strip_block_marker(trimmed)));
+    }
+    let mut out = String::with_capacity(markdown.len());
+    for line in lines {
+        let line = line.trim_end();
+        if line.is_empty() && (out.is_empty() || out.ends_with("\n\n")) {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// `---`, `***` or `___`, optionally spaced.
+fn is_rule(line: &str) -> bool {
+    let marks: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.chars().count() >= 3 && ['-', '*', '_'].iter().any(|mark| marks.chars().all(|c| c == *mark))
+}
+
+/// Remove the heading, quote and list markers at the start of a line.
+fn strip_block_marker(mut line: &str) -> &str {
+    loop {
+        let before = line;
+        line = line.trim_start();
+        if let Some(rest) = line.strip_prefix('>') {
+            line = rest;
+        }
+        else if line.starts_with('#') {
+            let rest = line.trim_start_matches('#');
+            if rest.is_empty() || rest.starts_with(' ') {
+                line = rest;
+            }
+        }
+        else if let Some(rest) =
+            line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).or_else(|| line.strip_prefix("+ "))
+        {
+            line = rest;
+        }
+        else {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits > 0 {
+                if let Some(rest) = line[digits..].strip_prefix(". ").or_else(|| line[digits..].strip_prefix(") ")) {
+                    line = rest;
+                }
+            }
+        }
+        if line == before {
+            return line;
+        }
+    }
+}
+
+/// Remove the inline syntax: emphasis, code spans, links, images, autolinks, math delimiters and escapes.
+fn strip_inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(|c| c.is_ascii_punctuation()) => {
+                out.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while(|c| **c == '`').count();
+                let content = i + run;
+                let close = (content..chars.len())
+                    .find(|&j| chars[j..].iter().take_while(|c| **c == '`').count() == run && (j == 0 || chars[j - 1] != '`'));
+                match close {
+                    Some(close) => {
+                        out.extend(&chars[content..close]);
+                        i = close + run;
+                    }
+                    None => {
+                        out.extend(&chars[i..content]);
+                        i = content;
+                    }
+                }
+                continue;
+            }
+            '!' if chars.get(i + 1) == Some(&'[') => {
+                if let Some((text, end)) = parse_link(&chars, i + 1) {
+                    out.push_str(&strip_inline(&text));
+                    i = end;
+                    continue;
+                }
+            }
+            '[' => {
+                if let Some((text, end)) = parse_link(&chars, i) {
+                    out.push_str(&strip_inline(&text));
+                    i = end;
+                    continue;
+                }
+            }
+            '<' => {
+                if let Some(close) = chars[i..].iter().position(|c| *c == '>') {
+                    let inner: String = chars[i + 1..i + close].iter().collect();
+                    if inner.contains("://") && !inner.contains(' ') {
+                        out.push_str(&inner);
+                        i += close + 1;
+                        continue;
+                    }
+                }
+            }
+            '*' | '$' => {
+                i += 1;
+                continue;
+            }
+            '~' if chars.get(i + 1) == Some(&'~') => {
+                i += 2;
+                continue;
+            }
+            // underscores inside words such as `snake_case` are text
+            '_' => {
+                let before = i.checked_sub(1).map(|j| chars[j]);
+                let after = chars.get(i + 1).copied();
+                if !(before.is_some_and(char::is_alphanumeric) && after.is_some_and(char::is_alphanumeric)) {
+                    i += 1;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+/// Parse `[text](destination)` starting at the `[`, returning the text and the index after the `)`.
+fn parse_link(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut depth = 0;
+    let mut close = None;
+    for (j, c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(j);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let mut depth = 0;
+    for (j, c) in chars.iter().enumerate().skip(close + 1) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((chars[start + 1..close].iter().collect(), j + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Telltale snippets of the languages [`guess_language`] knows.
+const LANGUAGE_MARKERS: [(&str, &[&str]); 10] = [
+    ("rust", &["fn ", "let mut ", "impl ", "pub fn ", "println!", "-> ", "&mut ", "use std::", "#[derive"]),
+    ("python", &["def ", "import ", "self.", "elif ", "print(", "__init__", "None", "lambda "]),
+    ("javascript", &["function ", "const ", "=> ", "console.log", "require(", "=== ", "undefined"]),
+    ("cpp", &["#include", "std::", "int main", "cout", "nullptr", "template<"]),
+    ("java", &["public class ", "System.out", "public static void", "private ", "import java."]),
+    ("go", &["func ", "package ", ":= ", "fmt.", "err != nil"]),
+    ("bash", &["#!/bin/", "echo ", "sudo ", "export ", "fi\n", "$ "]),
+    ("html", &["<div", "<html", "</", "<span", "<!DOCTYPE"]),
+    ("sql", &["SELECT ", "FROM ", "WHERE ", "INSERT INTO", "CREATE TABLE"]),
+    ("json", &["\": ", "{\n", "\",\n", "[\n"]),
+];
+
+/// Guess the language of a code snippet from the keywords it uses, for the info string of a fence.
+///
+/// Needs at least two distinct hints of the same language and gives up on ties, so it rather misses than misleads.
+///
+/// # Examples
+///
+/// ```
+/// # use zhihu_link::utils::guess_language;
+/// assert_eq!(guess_language("fn main() {\n    let mut x = 1;\n}"), Some("rust"));
+/// assert_eq!(guess_language("1 + 1"), None);
+/// ```
+pub fn guess_language(code: &str) -> Option<&'static str> {
+    let mut scores: Vec<(&str, usize)> = LANGUAGE_MARKERS
+        .iter()
+        .map(|(language, markers)| (*language, markers.iter().filter(|marker| code.contains(*marker)).count()))
+        .filter(|(_, score)| *score >= 2)
+        .collect();
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    match scores.as_slice() {
+        [(language, _)] => Some(language),
+        [(language, best), (_, second), ..] if best > second => Some(language),
+        _ => None,
+    }
+}
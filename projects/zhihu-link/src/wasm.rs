@@ -0,0 +1,20 @@
+use crate::{RenderOptions, ZhihuAnswer, ZhihuArticle, ZhihuResult};
+use htmler::{Html, Selector};
+use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
+
+/// Render the html of a zhihu answer or column article, fetched by the caller, to markdown.
+///
+/// Pages with a column title or body are rendered as an article, any other as an answer, both leniently.
+#[wasm_bindgen]
+pub fn render(html: &str) -> Result<String, JsValue> {
+    render_page(html).map_err(|error| JsValue::from_str(&format!("{error:?}")))
+}
+
+fn render_page(html: &str) -> ZhihuResult<String> {
+    let selector = Selector::new("h1.Post-Title, div.Post-RichText");
+    let is_article = Html::parse_document(html).select(&selector).next().is_some();
+    match is_article {
+        true => Ok(ZhihuArticle::from_html(html, RenderOptions::default())?.to_string()),
+        false => Ok(ZhihuAnswer::from_html(html, RenderOptions::default())?.to_string()),
+    }
+}
@@ -0,0 +1,114 @@
+use super::ColumnExport;
+use crate::{
+    Progress, ZhihuClient, ZhihuResult,
+    client::paginate,
+    utils::{article_url, json_id},
+};
+use futures::{StreamExt, stream};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// How many articles of a column are listed per request.
+const PAGE_SIZE: usize = 20;
+
+/// 将整个专栏归档到一个目录, 每篇文章保存为 `<文章 ID>.md`
+///
+/// 目录中与 [`ColumnExport`] 一样保存 `manifest.json`, 再次归档时只获取其中还没有列出的文章
+///
+/// # Examples
+///
+/// ```no_run
+/// # use zhihu_link::ZhihuColumn;
+/// # async fn run() -> zhihu_link::ZhihuResult<()> {
+/// let new_articles =
+///     ZhihuColumn::new("https://zhuanlan.zhihu.com/c_1234567890").archive("column").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZhihuColumn {
+    slug: String,
+    export: ColumnExport,
+}
+
+impl ZhihuColumn {
+    /// 通过专栏的 slug 创建, 如 `c_1234567890`, 也可以是 `zhuanlan.zhihu.com/c_1234567890` 这样的链接, 使用默认的客户端
+    pub fn new(slug: &str) -> Self {
+        Self::with_client(ZhihuClient::default(), slug)
+    }
+    /// 通过专栏的 slug 创建, 使用给定的客户端
+    pub fn with_client(client: ZhihuClient, slug: &str) -> Self {
+        let path = slug.split(['?', '#']).next().unwrap_or(slug);
+        let slug = path.trim_end_matches('/').rsplit('/').next().unwrap_or(path);
+        Self { slug: slug.to_string(), export: ColumnExport::new(client) }
+    }
+    /// 获取和渲染文章的方式, 使用其中的渲染选项和并发数
+    pub fn export(mut self, export: ColumnExport) -> Self {
+        self.export = export;
+        self
+    }
+    /// 专栏的 slug
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+    /// 列出专栏全部文章的 ID 和链接, 按专栏接口返回的顺序
+    pub async fn articles(&self) -> ZhihuResult<Vec<(usize, String)>> {
+        let client = &self.export.client;
+        let items = paginate(client, &client.api_url(&format!("/columns/{}/items", self.slug)), PAGE_SIZE).await?;
+        let articles = items.iter().filter(|item| item["type"].as_str().is_none_or(|kind| kind == "article"));
+        Ok(articles
+            .filter_map(|item| {
+                let id = json_id(&item["id"])?;
+                Some((id, item["url"].as_str().map_or_else(|| article_url(id), str::to_string)))
+            })
+            .collect())
+    }
+    /// 获取 `dir/manifest.json` 中还没有列出的文章, 并把它们加入其中
+    ///
+    /// 返回每篇新文章写入的文件或失败的原因, 按专栏中的顺序, 失败的文章不写入 `manifest.json`, 下次归档时重试
+    ///
+    /// 每篇新文章结束时调用 [`ColumnExport`] 的进度回调
+    pub async fn archive<P>(&self, dir: P) -> ZhihuResult<Vec<ZhihuResult<PathBuf>>>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let manifest_path = dir.join("manifest.json");
+        let old: Vec<Value> = match std::fs::read(&manifest_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(_) => vec![],
+        };
+        let archived: HashSet<u64> = old.iter().filter_map(|entry| entry["id"].as_u64()).collect();
+        let articles = self.articles().await?;
+        let fresh: Vec<&(usize, String)> = articles.iter().filter(|(id, _)| !archived.contains(&(*id as u64))).collect();
+        let total = fresh.len();
+        let mut done = 0;
+        let mut reports: Vec<(usize, ZhihuResult<(PathBuf, Value)>)> = stream::iter(fresh.into_iter().enumerate())
+            .map(|(index, (id, url))| async move {
+                let name = format!("{id}.md");
+                let path = dir.join(&name);
+                let entry = self.export.export_one(url, &path, &name).await.map(|mut entry| {
+                    entry["id"] = Value::from(*id);
+                    (path, entry)
+                });
+                (index, entry)
+            })
+            .buffer_unordered(self.export.concurrency)
+            .inspect(|_| {
+                done += 1;
+                self.export.report(Progress { done, total });
+            })
+            .collect()
+            .await;
+        reports.sort_by_key(|(index, _)| *index);
+        // new articles first, as the column lists its latest articles first
+        let mut manifest: Vec<&Value> = reports.iter().filter_map(|(_, report)| Some(&report.as_ref().ok()?.1)).collect();
+        manifest.extend(&old);
+        std::fs::write(manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        Ok(reports.into_iter().map(|(_, report)| report.map(|(path, _)| path)).collect())
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    ContentKind, Progress, RenderOptions, UrlDispatcher, ZhihuArticle, ZhihuClient, ZhihuResult, client::unix_time,
+    progress::ProgressHook,
+};
+use futures::{StreamExt, stream};
+use serde_json::{Value, json};
+use std::{
+    fmt::{Debug, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// 将专栏的文章逐篇导出为 markdown 文件
+///
+/// 文件名以文章在专栏中的序号开头, 无论下载按什么顺序完成, 文件都按专栏的顺序排列
+///
+/// # Examples
+///
+/// ```no_run
+/// # use zhihu_link::{ColumnExport, ZhihuClient};
+/// # async fn run() -> zhihu_link::ZhihuResult<()> {
+/// let urls = ["https://zhuanlan.zhihu.com/p/620388361", "https://zhuanlan.zhihu.com/p/620388362"];
+/// let reports =
+///     ColumnExport::new(ZhihuClient::default()).concurrency(2).export(urls, "column").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ColumnExport {
+    pub(super) client: ZhihuClient,
+    options: RenderOptions,
+    pub(super) concurrency: usize,
+    progress: Option<ProgressHook>,
+}
+
+impl Debug for ColumnExport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColumnExport")
+            .field("client", &self.client)
+            .field("options", &self.options)
+            .field("concurrency", &self.concurrency)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl ColumnExport {
+    /// 使用给定的客户端导出, 默认同时获取 4 篇文章
+    pub fn new(client: ZhihuClient) -> Self {
+        Self { client, options: RenderOptions::default(), concurrency: 4, progress: None }
+    }
+    /// 渲染文章的选项
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// 同时获取的文章数, 至少为 1, 默认为 4
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+    /// 每篇文章结束时调用 `hook`, 无论保存成功还是失败
+    pub fn progress<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(hook));
+        self
+    }
+    /// 获取按专栏顺序给出的文章 `urls`, 写入 `dir` 目录
+    ///
+    /// 返回每篇文章写入的文件或失败的原因, 按专栏中的顺序, 只有创建 `dir` 或写入 `manifest.json` 失败时中止
+    ///
+    /// `manifest.json` 列出每篇导出文章的 `id`, `title`, `file`, `url` 和以 unix 秒计的 `fetched_at`
+    pub async fn export<I, S, P>(&self, urls: I, dir: P) -> ZhihuResult<Vec<ZhihuResult<PathBuf>>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let urls: Vec<S> = urls.into_iter().collect();
+        let width = urls.len().to_string().len().max(3);
+        let total = urls.len();
+        let mut done = 0;
+        let mut reports: Vec<(usize, ZhihuResult<(PathBuf, Value)>)> = stream::iter(urls.iter().enumerate())
+            .map(|(index, url)| async move {
+                let name = file_name(index + 1, width, url.as_ref());
+                let path = dir.join(&name);
+                (index, self.export_one(url.as_ref(), &path, &name).await.map(|entry| (path, entry)))
+            })
+            .buffer_unordered(self.concurrency)
+            .inspect(|_| {
+                done += 1;
+                self.report(Progress { done, total });
+            })
+            .collect()
+            .await;
+        reports.sort_by_key(|(index, _)| *index);
+        let manifest: Vec<&Value> = reports.iter().filter_map(|(_, report)| Some(&report.as_ref().ok()?.1)).collect();
+        std::fs::write(dir.join("manifest.json"), serde_json::to_string_pretty(&manifest)?)?;
+        Ok(reports.into_iter().map(|(_, report)| report.map(|(path, _)| path)).collect())
+    }
+    /// Tell the progress hook, if any, how far the export has got.
+    pub(super) fn report(&self, progress: Progress) {
+        if let Some(hook) = &self.progress {
+            hook(progress);
+        }
+    }
+    /// Save one article, returning its manifest entry.
+    pub(super) async fn export_one(&self, url: &str, path: &Path, name: &str) -> ZhihuResult<Value> {
+        // the manifest always records the fetch time, the file only with provenance, both the same
+        let fetched_at = unix_time();
+        let html = self.client.get_text(url).await?;
+        let article = ZhihuArticle::from_html(&html, self.options.clone())?
+            .with_fetched_at(self.options.provenance.then_some(fetched_at));
+        article.save(path)?;
+        let id = match UrlDispatcher::classify(article.permalink().unwrap_or(url)) {
+            Some(ContentKind::Article { id }) => Some(id),
+            _ => None,
+        };
+        let column = article.column().map(|column| &column.name);
+        Ok(json!({ "id": id, "title": article.title(), "column": column, "file": name, "url": url, "fetched_at": fetched_at }))
+    }
+}
+
+/// `<sequence>-<article id>.md`, or `<sequence>.md` when the url is not a zhihu article.
+fn file_name(sequence: usize, width: usize, url: &str) -> String {
+    match UrlDispatcher::classify(url) {
+        Some(ContentKind::Article { id }) => format!("{sequence:0width$}-{id}.md"),
+        _ => format!("{sequence:0width$}.md"),
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{
+    Comment, ContentKind, RelatedLink, RenderOptions, UrlDispatcher, ZhihuError, ZhihuResult,
+    renders::{
+        AuthorInfo, Document, Engagement, MarkdownRenderer, Reference, author_info, content_item, content_times,
+        initial_entity, page_title, related_links, render_section, topics, write_document,
+    },
+    utils::{article_url, reading_minutes},
+};
+#[cfg(feature = "network")]
+use crate::{
+    Progress, ZhihuClient,
+    assets::{AssetReport, localize_images},
+    comments::fetch_comments,
+};
+use htmler::{Html, Selector};
+use std::{
+    fmt::{Display, Formatter},
+    io::Write as _,
+    path::Path,
+    str::FromStr,
+};
+
+#[cfg(feature = "network")]
+mod column;
+#[cfg(feature = "network")]
+mod export;
+
+#[cfg(feature = "network")]
+pub use self::{column::ZhihuColumn, export::ColumnExport};
+
+#[derive(Debug, Clone)]
+pub struct ZhihuArticle {
+    title: String,
+    content: String,
+    /// The html of the rich text, kept to render sections on demand.
+    source: String,
+    permalink: Option<String>,
+    unhandled_tags: Vec<String>,
+    images: Vec<String>,
+    related: Vec<RelatedLink>,
+    references: Vec<Reference>,
+    column: Option<Column>,
+    author_info: Option<AuthorInfo>,
+    engagement: Engagement,
+    topics: Vec<String>,
+    published: Option<String>,
+    edited: Option<String>,
+    /// When the page was fetched, in seconds since the unix epoch, unknown for html parsed from elsewhere.
+    fetched_at: Option<u64>,
+    comments: Vec<Comment>,
+    options: RenderOptions,
+}
+
+/// The column an article is published in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    /// The name of the column.
+    pub name: String,
+    /// The absolute url of the column.
+    pub url: String,
+}
+
+impl Default for ZhihuArticle {
+    fn default() -> Self {
+        Self {
+            title: "".to_string(),
+            content: "".to_string(),
+            source: "".to_string(),
+            permalink: None,
+            unhandled_tags: vec![],
+            images: vec![],
+            related: vec![],
+            references: vec![],
+            column: None,
+            author_info: None,
+            engagement: Engagement::default(),
+            topics: vec![],
+            published: None,
+            edited: None,
+            fetched_at: None,
+            comments: vec![],
+            options: RenderOptions::default(),
+        }
+    }
+}
+
+impl Display for ZhihuArticle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let byline = self.author_info.as_ref().filter(|_| self.options.author_byline).map(AuthorInfo::byline);
+        let document = Document {
+            title: &self.title,
+            intro: None,
+            byline: byline.as_deref(),
+            author: self.author_info.as_ref().map(|info| info.name.as_str()),
+            topics: &self.topics,
+            content: &self.content,
+            permalink: self.permalink.as_deref(),
+            related: &self.related,
+            references: &self.references,
+            column: self.column.as_ref(),
+            engagement: self.engagement,
+            published: self.published.as_deref(),
+            edited: self.edited.as_deref(),
+            fetched_at: self.fetched_at,
+            repost_forbidden: false,
+            comments: &self.comments,
+        };
+        write_document(f, &self.options, &document)
+    }
+}
+
+impl FromStr for ZhihuArticle {
+    type Err = ZhihuError;
+
+    fn from_str(html: &str) -> Result<Self, Self::Err> {
+        Self::from_html(html, RenderOptions::default())
+    }
+}
+
+impl ZhihuArticle {
+    #[cfg(feature = "network")]
+    /// 通过文章 ID 获取知乎专栏文章, 并渲染为 markdown
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use zhihu_link::ZhihuArticle;
+    /// # async fn run() -> zhihu_link::ZhihuResult<()> {
+    /// let article = ZhihuArticle::new(620388361).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new(id: usize) -> ZhihuResult<Self> {
+        Self::with_client(&ZhihuClient::default(), id).await
+    }
+    #[cfg(feature = "network")]
+    /// 使用给定的客户端获取知乎专栏文章, 以便控制请求头, 代理或 cookie
+    pub async fn with_client(client: &ZhihuClient, id: usize) -> ZhihuResult<Self> {
+        let html = client.get_text(&article_url(id)).await?;
+        let mut out: Self = html.parse()?;
+        out.permalink.get_or_insert_with(|| article_url(id));
+        Ok(out)
+    }
+    /// 解析知乎专栏文章的 html, 并按照给定的选项渲染为 markdown
+    pub fn from_html(html: &str, options: RenderOptions) -> ZhihuResult<Self> {
+        let mut empty = Self { options, ..Self::default() };
+        empty.do_parse(html)?;
+        Ok(empty)
+    }
+    /// 设置抓取页面的时间, 以 unix 秒计, 开启 [`RenderOptions::provenance`] 时写入来源注释
+    pub fn with_fetched_at(mut self, fetched_at: Option<u64>) -> Self {
+        self.fetched_at = fetched_at;
+        self
+    }
+    /// 获取知乎专栏文章的 html, 非成功的状态码返回 [`ZhihuError::HttpStatus`]
+    #[cfg(feature = "network")]
+    pub async fn request(id: usize) -> ZhihuResult<String> {
+        ZhihuClient::default().get_text(&article_url(id)).await
+    }
+    /// 获取知乎专栏文章正文的 html, 不转换为 markdown, 找不到正文时返回 [`ZhihuError::MissingContent`]
+    #[cfg(feature = "network")]
+    pub async fn request_content_html(id: usize) -> ZhihuResult<String> {
+        let html = Html::parse_document(&Self::request(id).await?);
+        match html.select(&Selector::new("div.Post-RichText")).next() {
+            Some(node) => Ok(node.inner_html()),
+            None => Err(ZhihuError::MissingContent("div.Post-RichText".to_string())),
+        }
+    }
+    /// 文章的标题
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    /// 文章的永久链接, 形如 `https://zhuanlan.zhihu.com/p/<id>`
+    pub fn permalink(&self) -> Option<&str> {
+        self.permalink.as_deref()
+    }
+    /// 估计的阅读时间, 单位为分钟
+    pub fn reading_minutes(&self) -> usize {
+        reading_minutes(&self.content)
+    }
+    /// 正文的 html, 已去掉 [`RenderOptions::remove_selectors`] 匹配的部分, 可交给其他转换器
+    pub fn content_html(&self) -> &str {
+        &self.source
+    }
+    /// 转换时未能识别, 只保留了文本的标签
+    pub fn unhandled_tags(&self) -> &[String] {
+        &self.unhandled_tags
+    }
+    /// 文章所属的话题, 开启 [`RenderOptions::frontmatter`] 时写入 `tags`
+    pub fn topics(&self) -> &[String] {
+        &self.topics
+    }
+    /// 页面推荐的相关内容, 开启 [`RenderOptions::related`] 时作为附录输出
+    pub fn related(&self) -> &[RelatedLink] {
+        &self.related
+    }
+    /// 发布的时间, 即页面上 `发布于` 之后的文字, 或 `datePublished` 元数据
+    pub fn published_at(&self) -> Option<&str> {
+        self.published.as_deref()
+    }
+    /// 最后编辑的时间, 即页面上 `编辑于` 之后的文字, 或 `dateModified` 元数据, 未编辑过的文章为 `None`
+    pub fn edited_at(&self) -> Option<&str> {
+        self.edited.as_deref()
+    }
+    /// 赞同数, 评论数, 收藏数和分享数, 优先读取按钮上的数字, 其次是页面数据
+    pub fn engagement(&self) -> Engagement {
+        self.engagement
+    }
+    /// 作者的名字, 简介, 头像和主页链接
+    pub fn author_info(&self) -> Option<&AuthorInfo> {
+        self.author_info.as_ref()
+    }
+    /// 文章所属的专栏, 开启 [`RenderOptions::frontmatter`] 时写入 `column` 和 `column_url`
+    pub fn column(&self) -> Option<&Column> {
+        self.column.as_ref()
+    }
+    /// 文章的评论, 调用 [`Self::with_comments`] 或 [`Self::fetch_comments`] 之后才有
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+    #[cfg(feature = "network")]
+    /// 为 `true` 时使用默认的客户端获取评论及其全部回复, 见 [`Self::fetch_comments`]
+    pub async fn with_comments(mut self, with_comments: bool) -> ZhihuResult<Self> {
+        if with_comments {
+            self.fetch_comments(&ZhihuClient::default()).await?;
+        }
+        Ok(self)
+    }
+    #[cfg(feature = "network")]
+    /// 通过评论接口获取评论及其全部回复, 之后渲染的 markdown 在文末附上 `## 评论` 一节
+    pub async fn fetch_comments(&mut self, client: &ZhihuClient) -> ZhihuResult<()> {
+        let id = match self.permalink.as_deref().and_then(UrlDispatcher::classify) {
+            Some(ContentKind::Article { id }) => id,
+            _ => return Err(ZhihuError::MissingContent("link[rel=\"canonical\"]".to_string())),
+        };
+        self.comments = fetch_comments(client, "articles", id).await?;
+        Ok(())
+    }
+    /// 只渲染第一个匹配选择器的部分, 匹配到标题时包括直到下一个同级标题的整节
+    pub fn render_section(&self, selector: &Selector) -> ZhihuResult<Option<String>> {
+        render_section(&self.options, &self.source, selector)
+    }
+    pub fn save<P>(&self, path: P) -> ZhihuResult<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(self.to_string().as_bytes())?;
+        Ok(())
+    }
+    #[cfg(feature = "network")]
+    /// 保存到 `dir` 目录, markdown 写入 `dir/index.md`, 图片下载到 `dir/assets` 并改写为相对链接
+    ///
+    /// 下载失败的图片保留原始链接, 返回的报告列出成功与失败的图片, 以便重试或记录
+    pub async fn save_with_assets<P>(&self, dir: P) -> ZhihuResult<AssetReport>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        self.save_with_assets_progress(&ZhihuClient::default(), dir.join("index.md"), dir.join("assets"), |_| {}).await
+    }
+    #[cfg(feature = "network")]
+    /// 同 [`Self::save_with_assets`], 但 markdown 写入 `path`, 图片下载到 `assets`
+    ///
+    /// 使用给定的客户端下载图片, 每张图片下载结束后调用 `progress` 报告进度
+    pub async fn save_with_assets_progress<P, Q, F>(
+        &self,
+        client: &ZhihuClient,
+        path: P,
+        assets: Q,
+        progress: F,
+    ) -> ZhihuResult<AssetReport>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        F: Fn(Progress) + Send + Sync,
+    {
+        let (markdown, report) =
+            localize_images(client, self.to_string(), &self.images, path.as_ref(), assets.as_ref(), &progress).await?;
+        tokio::fs::write(path, markdown).await?;
+        Ok(report)
+    }
+    fn do_parse(&mut self, html: &str) -> ZhihuResult<()> {
+        let mut html = Html::parse_document(html);
+        self.options.prune(&mut html);
+        self.extract_title(&html)?;
+        self.extract_permalink(&html)?;
+        self.extract_column(&html)?;
+        self.author_info = author_info(&html);
+        (self.published, self.edited) = content_times(&html);
+        self.extract_engagement(&html)?;
+        self.extract_content(&html)?;
+        self.topics = topics(&html);
+        self.related = related_links(&html);
+        Ok(())
+    }
+    fn extract_title(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new("h1.Post-Title");
+        let title = html.select(&selector).next().map(|node| node.inner_text().trim().to_string());
+        match title.filter(|title| !title.is_empty()).or_else(|| page_title(html)) {
+            Some(title) if !title.is_empty() => self.title = title,
+            _ => self.options.missing_content("h1.Post-Title")?,
+        }
+        Ok(())
+    }
+    fn extract_permalink(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new(r#"link[rel="canonical"]"#);
+        for node in html.select(&selector) {
+            if let Some(ContentKind::Article { id }) = UrlDispatcher::classify(node.get_attribute("href")) {
+                self.permalink = Some(article_url(id));
+                break;
+            }
+        }
+        Ok(())
+    }
+    fn extract_column(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new("a.ColumnLink[href]");
+        let title = Selector::new(".ColumnLink-title");
+        let base = url::Url::parse("https://zhuanlan.zhihu.com").expect("valid base url");
+        for node in html.select(&selector) {
+            let name = match node.select(&title).next() {
+                Some(title) => title.inner_text(),
+                None => node.inner_text(),
+            };
+            let name = name.trim();
+            match base.join(node.get_attribute("href")) {
+                Ok(url) if !name.is_empty() => {
+                    self.column = Some(Column { name: name.to_string(), url: url.into() });
+                    break;
+                }
+                _ => continue,
+            }
+        }
+        Ok(())
+    }
+    fn extract_engagement(&mut self, html: &Html) -> ZhihuResult<()> {
+        let id = match self.permalink.as_deref().and_then(UrlDispatcher::classify) {
+            Some(ContentKind::Article { id }) => Some(id),
+            _ => None,
+        };
+        let item = content_item(html, "div.Post-RichText", id);
+        self.engagement = Engagement::extract(item, initial_entity(html, "articles", id).as_ref());
+        Ok(())
+    }
+    fn extract_content(&mut self, html: &Html) -> ZhihuResult<()> {
+        let selector = Selector::new("div.Post-RichText");
+        match html.select(&selector).next() {
+            Some(node) => {
+                let mut renderer = MarkdownRenderer::new(&self.options);
+                renderer.render_children(node)?;
+                self.source = node.inner_html();
+                self.content = renderer.content;
+                self.unhandled_tags = renderer.unhandled_tags;
+                self.images = renderer.images;
+                self.references = renderer.references;
+            }
+            None => self.options.missing_content("div.Post-RichText")?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+//! Pages shared by the tests and the benches.
+
+/// An answer page with `content` as its body.
+pub fn answer_page(content: &str) -> String {
+    format!(
+        r#"<html><body><h1 class="QuestionHeader-title">标题</h1><span class="CopyrightRichText-richText">{content}</span></body></html>"#
+    )
+}
+
+/// A column article with `content` as its body, and a permalink.
+pub fn article_page(content: &str) -> String {
+    format!(
+        r#"<html><head><link rel="canonical" href="https://zhuanlan.zhihu.com/p/620388361"></head><body><h1 class="Post-Title">专栏标题</h1><div class="RichText ztext Post-RichText">{content}</div></body></html>"#
+    )
+}
@@ -0,0 +1,38 @@
+//! Helpers shared by the integration tests, each test file uses only a part of them.
+#![allow(dead_code)]
+
+mod fixtures;
+
+pub use self::fixtures::{answer_page, article_page};
+use std::{future::Future, sync::Arc};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Serve every request with the status and body `route` returns for its path, or a 404 for `None`.
+///
+/// `route` is given the address of the server too, for pages that link to the next one.
+pub async fn mock_server<F, R>(route: F) -> String
+where
+    F: Fn(String, String) -> R + Send + Sync + 'static,
+    R: Future<Output = Option<(u16, String)>> + Send + 'static,
+{
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+    let route = Arc::new(route);
+    let address = server.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let route = route.clone();
+            let address = address.clone();
+            tokio::spawn(async move {
+                let mut buffer = [0; 1024];
+                let n = socket.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+                let path = request.split(' ').nth(1).unwrap_or_default().to_string();
+                let (status, body) = route(address, path).await.unwrap_or((404, String::new()));
+                let head = format!("HTTP/1.1 {status} \r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                let _ = socket.write_all(format!("{head}{body}").as_bytes()).await;
+            });
+        }
+    });
+    server
+}
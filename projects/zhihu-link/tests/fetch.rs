@@ -0,0 +1,608 @@
+//! Fetching through a client, against local mock servers, and saving to disk.
+
+mod common;
+
+use common::{answer_page, article_page, mock_server};
+use std::str::FromStr;
+use zhihu_link::{RenderOptions, ZhihuAnswer, ZhihuArticle, ZhihuError};
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+// #[tokio::test]
+// async fn test_reqwest() {
+//     let answer = ZhihuAnswer::from_str(include_str!("../test.html")).unwrap();
+//     answer.save("test.md").await.unwrap();
+// }
+//
+// #[tokio::test]
+// async fn test_reqwest2() {
+//     let answer = ZhihuAnswer::request(347662352, 847873806).await.unwrap();
+//     let mut file = std::fs::File::create("test.html").unwrap();
+//     file.write_all(answer.as_bytes()).unwrap();
+// }
+
+#[tokio::test]
+async fn auto_uses_given_client() {
+    use zhihu_link::{ZhihuAuto, ZhihuClient};
+    // the proxy only records the tunnelled address and then refuses
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let proxy = mock_server(move |_, path| {
+        let _ = sender.send(path);
+        async { Some((502, String::new())) }
+    })
+    .await;
+    let inner = reqwest::Client::builder().proxy(reqwest::Proxy::all(proxy).unwrap()).build().unwrap();
+    let client = ZhihuClient::from(inner);
+    assert!(ZhihuAuto::with_client("https://zhuanlan.zhihu.com/p/620388361", &client).await.is_err());
+    assert_eq!(receiver.recv().await.unwrap(), "zhuanlan.zhihu.com:443");
+    assert!(matches!(
+        ZhihuAuto::with_client("https://www.zhihu.com/people/aster", &client).await,
+        Err(ZhihuError::UnsupportedUrl(_))
+    ));
+}
+
+#[tokio::test]
+async fn save_answers_by_author() {
+    use zhihu_link::{ZhihuClient, ZhihuQuestion};
+    let server = mock_server(|_, path| async move {
+        let body = match path.split('?').next().unwrap_or_default() {
+            "/questions/1" => r#"{"title":"问题","detail":""}"#,
+            // the feed repeats an answer, as overlapping pages do
+            "/questions/1/feeds" => {
+                r#"{"data":[
+                    {"target":{"type":"answer","id":10,"author":{"name":"a/b:c"},"voteup_count":1,"content":"<p>回答</p>"}},
+                    {"target":{"type":"answer","id":10,"author":{"name":"a/b:c"},"voteup_count":1,"content":"<p>回答</p>"}},
+                    {"target":{"type":"answer","id":20,"author":{"name":"知友"},"voteup_count":2,"content":"<p>回答</p>"}}
+                ],"paging":{"is_end":true}}"#
+            }
+            _ => return None,
+        };
+        Some((200, body.to_string()))
+    })
+    .await;
+    let question = ZhihuQuestion::with_client(ZhihuClient::default().api_base(&server), 1);
+    let dir = std::env::temp_dir().join(format!("zhihu-link-answers-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let paths = question.save_answers(&dir).await.unwrap();
+    let names: Vec<_> = paths.iter().map(|path| path.file_name().unwrap().to_string_lossy().to_string()).collect();
+    assert_eq!(names, ["a_b_c-10.md", "a_b_c-10-2.md", "知友-20.md"]);
+    let markdown = std::fs::read_to_string(&paths[2]).unwrap();
+    assert!(markdown.starts_with("# 问题\n"));
+    assert!(markdown.contains("## 知友") && !markdown.contains("a/b:c"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Serve `/ok.png` and its copy `/copy.png`, and answer 404 to everything else.
+async fn image_server() -> String {
+    mock_server(|_, path| async move { matches!(path.as_str(), "/ok.png" | "/copy.png").then(|| (200, "PNG".to_string())) })
+        .await
+}
+
+#[tokio::test]
+async fn save_with_assets() {
+    let server = image_server().await;
+    let html = answer_page(&format!(
+        r#"<figure><img src="data:image/svg+xml;utf8,&lt;svg&gt;" data-original="{server}/ok.png"><figcaption>示意图</figcaption></figure><figure><img src="{server}/missing.png"></figure>"#
+    ));
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    let dir = std::env::temp_dir().join(format!("zhihu-link-assets-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let report = answer.save_with_assets(&dir).await.unwrap();
+    let markdown = std::fs::read_to_string(dir.join("index.md")).unwrap();
+    let local = markdown.split("](assets/").nth(1).and_then(|s| s.split(')').next()).unwrap();
+    assert_eq!(report.downloaded, [(format!("{server}/ok.png"), local.to_string())]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, format!("{server}/missing.png"));
+    assert!(report.failed[0].1.contains("404"), "{:?}", report.failed);
+    assert!(markdown.contains("![示意图](assets/"));
+    assert_eq!(std::fs::read(dir.join("assets").join(local)).unwrap(), b"PNG");
+    assert!(markdown.contains(&format!("![]({server}/missing.png)")));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn pin_save_with_assets() {
+    use zhihu_link::ZhihuPin;
+    let server = image_server().await;
+    let html = format!(
+        r#"<div class="PinItem-content"><div class="RichText"><p>晚霞</p></div><div class="Pin-images"><img src="{server}/ok.png"></div></div>"#
+    );
+    let pin = ZhihuPin::from_str(&html).unwrap();
+    let dir = std::env::temp_dir().join(format!("zhihu-link-pin-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let report = pin.save_with_assets(&dir).await.unwrap();
+    let markdown = std::fs::read_to_string(dir.join("index.md")).unwrap();
+    assert!(markdown.contains(&format!("![](assets/{})", report.downloaded[0].1)), "{markdown}");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn save_with_assets_by_content() {
+    let server = image_server().await;
+    // the same picture under two urls is stored once
+    let html =
+        answer_page(&format!(r#"<figure><img src="{server}/ok.png"></figure><figure><img src="{server}/copy.png"></figure>"#));
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    let dir = std::env::temp_dir().join(format!("zhihu-link-content-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let report = answer.save_with_assets(&dir).await.unwrap();
+    assert_eq!(report.downloaded.len(), 2);
+    assert_eq!(report.downloaded[0].1, report.downloaded[1].1);
+    assert_eq!(std::fs::read_dir(dir.join("assets")).unwrap().count(), 1);
+    let markdown = std::fs::read_to_string(dir.join("index.md")).unwrap();
+    assert_eq!(markdown.matches(&format!("](assets/{})", report.downloaded[0].1)).count(), 2);
+    // a file cut short by an interrupted run is written again
+    let local = dir.join("assets").join(&report.downloaded[0].1);
+    std::fs::write(&local, b"PN").unwrap();
+    let again = answer.save_with_assets(&dir).await.unwrap();
+    assert_eq!(again.downloaded, report.downloaded);
+    assert_eq!(std::fs::read(&local).unwrap(), b"PNG");
+    assert_eq!(std::fs::read_dir(dir.join("assets")).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn save_with_assets_keeps_links() {
+    let server = image_server().await;
+    let html = answer_page(&format!(
+        r#"<p><a href="{server}/ok.png">原图</a></p><a href="{server}/ok.png"><img src="{server}/ok.png"></a>"#
+    ));
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    let dir = std::env::temp_dir().join(format!("zhihu-link-links-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let report = answer.save_with_assets(&dir).await.unwrap();
+    let markdown = std::fs::read_to_string(dir.join("index.md")).unwrap();
+    let local = &report.downloaded[0].1;
+    // only the image points at the local copy, the links keep the remote url
+    assert!(markdown.contains(&format!("[原图]({server}/ok.png)")), "{markdown}");
+    assert!(markdown.contains(&format!("[![](assets/{local})]({server}/ok.png)")), "{markdown}");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn save_with_assets_reports_progress() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use zhihu_link::ZhihuClient;
+    let server = image_server().await;
+    // the duplicate is downloaded once, the missing image still counts
+    let html = answer_page(&format!(
+        r#"<figure><img src="{server}/ok.png"></figure><figure><img src="{server}/ok.png"></figure><figure><img src="{server}/missing.png"></figure>"#
+    ));
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    let dir = std::env::temp_dir().join(format!("zhihu-link-progress-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let calls = AtomicUsize::new(0);
+    answer
+        .save_with_assets_progress(&ZhihuClient::default(), dir.join("answer.md"), dir.join("assets"), |progress| {
+            assert_eq!(progress.total, 2);
+            assert_eq!(progress.done, calls.fetch_add(1, Ordering::SeqCst) + 1);
+        })
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn save_with_assets_uses_given_client() {
+    use zhihu_link::ZhihuClient;
+    // only the proxy of the client knows the image
+    let proxy =
+        mock_server(|_, path| async move { (path == "http://pic.invalid/a.png").then(|| (200, "PNG".to_string())) }).await;
+    let inner = reqwest::Client::builder().proxy(reqwest::Proxy::http(proxy).unwrap()).build().unwrap();
+    let answer = ZhihuAnswer::from_str(&answer_page(r#"<figure><img src="http://pic.invalid/a.png"></figure>"#)).unwrap();
+    let dir = std::env::temp_dir().join(format!("zhihu-link-client-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let report = answer
+        .save_with_assets_progress(&ZhihuClient::from(inner), dir.join("answer.md"), dir.join("assets"), |_| {})
+        .await
+        .unwrap();
+    assert!(report.failed.is_empty(), "{:?}", report.failed);
+    assert_eq!(std::fs::read(dir.join("assets").join(&report.downloaded[0].1)).unwrap(), b"PNG");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn column_export_keeps_order() {
+    use zhihu_link::{ColumnExport, ZhihuClient};
+    // the first article is the slowest, the third one is missing
+    let server = mock_server(|_, path| async move {
+        let title = match path.as_str() {
+            "/p/1" => {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                "第一篇"
+            }
+            "/p/2" => "第二篇",
+            _ => return None,
+        };
+        Some((200, format!(r#"<h1 class="Post-Title">{title}</h1><div class="Post-RichText"><p>正文</p></div>"#)))
+    })
+    .await;
+    let dir = std::env::temp_dir().join(format!("zhihu-link-column-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let urls = [format!("{server}/p/1"), format!("{server}/p/2"), format!("{server}/p/3")];
+    let calls = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let seen = calls.clone();
+    let reports = ColumnExport::new(ZhihuClient::default())
+        .concurrency(2)
+        .progress(move |progress| seen.lock().unwrap().push((progress.done, progress.total)))
+        .export(&urls, &dir)
+        .await
+        .unwrap();
+    assert_eq!(*calls.lock().unwrap(), [(1, 3), (2, 3), (3, 3)]);
+    let first = reports[0].as_ref().unwrap();
+    let second = reports[1].as_ref().unwrap();
+    assert!(reports[2].is_err());
+    assert_eq!(first.file_name().unwrap(), "001.md");
+    assert_eq!(second.file_name().unwrap(), "002.md");
+    assert!(std::fs::read_to_string(first).unwrap().starts_with("# 第一篇"));
+    assert!(std::fs::read_to_string(second).unwrap().starts_with("# 第二篇"));
+    let manifest: serde_json::Value = serde_json::from_slice(&std::fs::read(dir.join("manifest.json")).unwrap()).unwrap();
+    let entries = manifest.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    for (entry, (title, file)) in entries.iter().zip([("第一篇", "001.md"), ("第二篇", "002.md")]) {
+        assert_eq!(entry["title"], title);
+        assert_eq!(entry["file"], file);
+        assert_eq!(entry["id"], serde_json::Value::Null);
+        assert!(entry["url"].as_str().unwrap().starts_with(&server));
+        assert!(entry["fetched_at"].as_u64().unwrap() > 0);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn http_status_errors() {
+    use zhihu_link::{ZhihuClient, ZhihuQuestion};
+    // a rate limited client gets a 403 page, which must not be parsed as content
+    let server = mock_server(|_, _| async {
+        Some((403, r#"{"data":[{"target":{"type":"answer","id":1}}],"paging":{"is_end":true}}"#.to_string()))
+    })
+    .await;
+    let question = ZhihuQuestion::with_client(ZhihuClient::default().api_base(&server), 58151047);
+    match question.answers_from_cursor(None).await {
+        Err(ZhihuError::HttpStatus { status, url }) => {
+            assert_eq!(status, 403);
+            assert!(url.starts_with(&format!("{server}/questions/58151047/feeds")));
+        }
+        other => panic!("{other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn resume_column_archive() {
+    use std::sync::{Arc, Mutex};
+    use zhihu_link::{ColumnExport, Progress, ZhihuClient, ZhihuColumn};
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let server = mock_server(move |server, path| {
+        let _ = sender.send(path.clone());
+        async move {
+            let body = match path.as_str() {
+                "/columns/c_42/items?offset=0&limit=20" => format!(
+                    r#"{{"data":[{{"type":"article","id":2,"url":"{server}/p/2"}},{{"type":"article","id":1,"url":"{server}/p/1"}}],"paging":{{"is_end":true}}}}"#
+                ),
+                "/p/2" => r#"<h1 class="Post-Title">新文章</h1><div class="Post-RichText"><p>正文</p></div>"#.to_string(),
+                _ => return None,
+            };
+            Some((200, body))
+        }
+    })
+    .await;
+    let dir = std::env::temp_dir().join(format!("zhihu-link-archive-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // the first article was archived by an earlier run
+    std::fs::write(dir.join("manifest.json"), r#"[{"id":1,"title":"旧文章","file":"1.md"}]"#).unwrap();
+    let client = ZhihuClient::default().api_base(&server);
+    let seen = Arc::new(Mutex::new(vec![]));
+    let hook = seen.clone();
+    let export = ColumnExport::new(client.clone()).progress(move |progress| hook.lock().unwrap().push(progress));
+    let column = ZhihuColumn::with_client(client, "https://zhuanlan.zhihu.com/c_42/").export(export);
+    assert_eq!(column.slug(), "c_42");
+    let reports = column.archive(&dir).await.unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(*seen.lock().unwrap(), [Progress { done: 1, total: 1 }]);
+    assert_eq!(reports[0].as_ref().unwrap(), &dir.join("2.md"));
+    assert!(std::fs::read_to_string(dir.join("2.md")).unwrap().starts_with("# 新文章"));
+    receiver.close();
+    let mut requested = vec![];
+    while let Some(path) = receiver.recv().await {
+        requested.push(path);
+    }
+    assert_eq!(requested, ["/columns/c_42/items?offset=0&limit=20", "/p/2"]);
+    let manifest: serde_json::Value = serde_json::from_slice(&std::fs::read(dir.join("manifest.json")).unwrap()).unwrap();
+    let ids: Vec<_> = manifest.as_array().unwrap().iter().map(|entry| entry["id"].as_u64().unwrap()).collect();
+    assert_eq!(ids, [2, 1]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn question_page_size() {
+    use zhihu_link::{ZhihuClient, ZhihuQuestion};
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let server = mock_server(move |_, path| {
+        let _ = sender.send(path);
+        async { Some((200, r#"{"data":[],"paging":{"is_end":true}}"#.to_string())) }
+    })
+    .await;
+    let question = ZhihuQuestion::with_client(ZhihuClient::default().api_base(&server), 42);
+    question.clone().page_size(5).answers_from_cursor(None).await.unwrap();
+    assert_eq!(receiver.recv().await.unwrap(), "/questions/42/feeds?limit=5");
+    // zhihu refuses pages larger than 20
+    question.clone().page_size(100).answers_from_cursor(None).await.unwrap();
+    assert_eq!(receiver.recv().await.unwrap(), "/questions/42/feeds?limit=20");
+    question.page_size(0).answers_from_cursor(None).await.unwrap();
+    assert_eq!(receiver.recv().await.unwrap(), "/questions/42/feeds?limit=1");
+}
+
+#[test]
+fn question_from_url() {
+    use zhihu_link::{ZhihuClient, ZhihuQuestion};
+    let question = ZhihuQuestion::from_url(ZhihuClient::default(), "https://www.zhihu.com/question/58151047").unwrap();
+    assert_eq!(question.id(), 58151047);
+    // the link of an answer archives the whole discussion it belongs to
+    let answer = "https://www.zhihu.com/question/58151047/answer/1";
+    assert_eq!(ZhihuQuestion::from_url(ZhihuClient::default(), answer).unwrap().id(), 58151047);
+    assert!(matches!(
+        ZhihuQuestion::from_url(ZhihuClient::default(), "https://zhuanlan.zhihu.com/p/620388361"),
+        Err(ZhihuError::UnsupportedUrl(_))
+    ));
+}
+
+#[tokio::test]
+async fn collection_items() {
+    use zhihu_link::{ContentKind, ZhihuClient, ZhihuCollection};
+    let server = mock_server(|_, path| async move {
+        let body = match path.as_str() {
+            "/collections/9/items?offset=0&limit=20" => {
+                r#"{"data":[{"content":{"type":"answer","id":2,"question":{"id":1}}},{"content":{"type":"pin","id":3}}],"paging":{"is_end":false}}"#
+            }
+            "/collections/9/items?offset=2&limit=20" => {
+                r#"{"data":[{"content":{"type":"article","id":"620388361"}}],"paging":{"is_end":true}}"#
+            }
+            _ => return None,
+        };
+        Some((200, body.to_string()))
+    })
+    .await;
+    let collection = ZhihuCollection::with_client(ZhihuClient::default().api_base(&server), 9);
+    // the pin is skipped, the article of the second page follows
+    assert_eq!(
+        collection.items().await.unwrap(),
+        [ContentKind::Answer { question: 1, answer: 2 }, ContentKind::Article { id: 620388361 }]
+    );
+}
+
+#[tokio::test]
+async fn collection_export_interval() {
+    use zhihu_link::{ZhihuClient, ZhihuCollection};
+    // the server doubles as the proxy, which lists the collection and refuses the pages of zhihu
+    let server = mock_server(|_, path| async move {
+        path.ends_with("/collections/9/items?offset=0&limit=20").then(|| {
+            (200, r#"{"data":[{"content":{"type":"answer","id":2,"question":{"id":1}}},{"content":{"type":"article","id":3}}],"paging":{"is_end":true}}"#.to_string())
+        })
+    })
+    .await;
+    let inner = reqwest::Client::builder().proxy(reqwest::Proxy::all(&server).unwrap()).build().unwrap();
+    let collection = ZhihuCollection::with_client(ZhihuClient::from(inner).api_base(&server), 9)
+        .interval(std::time::Duration::from_millis(300));
+    let dir = std::env::temp_dir().join(format!("zhihu-link-interval-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let start = std::time::Instant::now();
+    let reports = collection.export(&dir).await.unwrap();
+    assert!(start.elapsed() >= std::time::Duration::from_millis(300));
+    assert!(reports.iter().all(|report| report.is_err()));
+    assert_eq!(std::fs::read_to_string(dir.join("index.md")).unwrap(), "# 收藏夹 9\n\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn user_content() {
+    use zhihu_link::{ContentKind, ZhihuClient, ZhihuUser};
+    let server = mock_server(|_, path| async move {
+        let body = match path.as_str() {
+            "/members/aster/answers?offset=0&limit=20" => {
+                r#"{"data":[{"id":2,"question":{"id":1}},{"id":"4","question":{"id":"3"}}],"paging":{"is_end":false}}"#
+            }
+            "/members/aster/answers?offset=2&limit=20" => r#"{"data":[{"id":6,"question":{"id":5}}],"paging":{"is_end":true}}"#,
+            "/members/aster/articles?offset=0&limit=20" => r#"{"data":[{"id":620388361}],"paging":{"is_end":true}}"#,
+            _ => return None,
+        };
+        Some((200, body.to_string()))
+    })
+    .await;
+    let user = ZhihuUser::with_client(ZhihuClient::default().api_base(&server), "aster");
+    assert_eq!(
+        user.answers().await.unwrap(),
+        [
+            ContentKind::Answer { question: 1, answer: 2 },
+            ContentKind::Answer { question: 3, answer: 4 },
+            ContentKind::Answer { question: 5, answer: 6 }
+        ]
+    );
+    assert_eq!(user.articles().await.unwrap(), [ContentKind::Article { id: 620388361 }]);
+}
+
+#[tokio::test]
+async fn user_token_stays_in_dir() {
+    use zhihu_link::{ZhihuClient, ZhihuUser};
+    let server = mock_server(|_, _| async { Some((200, r#"{"data":[],"paging":{"is_end":true}}"#.to_string())) }).await;
+    let dir = std::env::temp_dir().join(format!("zhihu-link-user-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let user = ZhihuUser::with_client(ZhihuClient::default().api_base(&server), "../x");
+    assert!(user.export_answers(dir.join("backup")).await.unwrap().is_empty());
+    assert!(dir.join("backup").join("_x").join("answers").is_dir());
+    assert!(!dir.join("x").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn answer_comments() {
+    use zhihu_link::ZhihuClient;
+    let server = mock_server(|server, path| async move {
+        let body = match path.as_str() {
+            "/answers/2/root_comments?order=normal&limit=20&offset=0" => format!(
+                r#"{{"data":[{{"author":{{"member":{{"name":"张三"}}}},"content":"<p>说得好<br>第二行</p>","child_comments":[{{"author":{{"member":{{"name":"李四"}}}},"content":"同意"}}]}}],"paging":{{"is_end":false,"next":"{server}/answers/2/root_comments?order=normal&limit=20&offset=20"}}}}"#
+            ),
+            "/answers/2/root_comments?order=normal&limit=20&offset=20" => {
+                r#"{"data":[{"author":{"name":"王五"},"content":"路过"}],"paging":{"is_end":true}}"#.to_string()
+            }
+            _ => return None,
+        };
+        Some((200, body))
+    })
+    .await;
+    let html = r#"<html><head><link rel="canonical" href="https://www.zhihu.com/question/1/answer/2"></head><body><span class="CopyrightRichText-richText"><p>回答</p></span></body></html>"#;
+    let mut answer = ZhihuAnswer::from_str(html).unwrap();
+    answer.fetch_comments(&ZhihuClient::default().api_base(&server)).await.unwrap();
+    assert_eq!(answer.comments().len(), 2);
+    assert!(
+        answer
+            .to_string()
+            .ends_with("回答\n\n## 评论\n\n> **张三**: 说得好\n> 第二行\n>\n> > **李四**: 同意\n\n> **王五**: 路过\n"),
+        "{answer}"
+    );
+}
+
+#[tokio::test]
+async fn stuck_comment_cursor() {
+    use zhihu_link::ZhihuClient;
+    // the next page is the page itself
+    let server = mock_server(|server, _| async move {
+        Some((
+            200,
+            format!(
+                r#"{{"data":[{{"author":{{"name":"张三"}},"content":"沙发"}}],"paging":{{"is_end":false,"next":"{server}/answers/2/root_comments?order=normal&limit=20&offset=0"}}}}"#
+            ),
+        ))
+    })
+    .await;
+    let html = r#"<html><head><link rel="canonical" href="https://www.zhihu.com/question/1/answer/2"></head><body><span class="CopyrightRichText-richText"><p>回答</p></span></body></html>"#;
+    let mut answer = ZhihuAnswer::from_str(html).unwrap();
+    answer.fetch_comments(&ZhihuClient::default().api_base(&server)).await.unwrap();
+    assert_eq!(answer.comments().len(), 1);
+}
+
+#[tokio::test]
+async fn article_comment_replies() {
+    use zhihu_link::ZhihuClient;
+    let server = mock_server(|_, path| async move {
+        let body = match path.as_str() {
+            // only the first of the two replies is embedded
+            "/articles/620388361/root_comments?order=normal&limit=20&offset=0" => {
+                r#"{"data":[{"id":11,"author":{"name":"张三"},"content":"好文","child_comment_count":2,"child_comments":[{"author":{"name":"李四"},"content":"一"}]}],"paging":{"is_end":true}}"#
+            }
+            "/comments/11/child_comments?limit=20&offset=0" => {
+                r#"{"data":[{"author":{"name":"李四"},"content":"一"},{"author":{"name":"王五"},"content":"二"}],"paging":{"is_end":true}}"#
+            }
+            _ => return None,
+        };
+        Some((200, body.to_string()))
+    })
+    .await;
+    let mut article = ZhihuArticle::from_str(&article_page("<p>正文</p>")).unwrap();
+    article.fetch_comments(&ZhihuClient::default().api_base(&server)).await.unwrap();
+    let replies: Vec<&str> = article.comments()[0].replies.iter().map(|reply| reply.author.as_str()).collect();
+    assert_eq!(replies, ["李四", "王五"]);
+    assert!(article.to_string().contains("## 评论\n\n> **张三**: 好文\n>\n> > **李四**: 一\n>\n> > **王五**: 二\n"));
+}
+
+#[tokio::test]
+async fn with_comments() {
+    let article = ZhihuArticle::from_str(&article_page("<p>正文</p>")).unwrap();
+    assert!(article.with_comments(false).await.unwrap().comments().is_empty());
+    // without a permalink there is no id to ask the comments of, nothing is fetched
+    let answer = ZhihuAnswer::from_str(&answer_page("<p>回答</p>")).unwrap();
+    assert!(matches!(answer.with_comments(true).await, Err(ZhihuError::MissingContent(_))));
+}
+
+#[tokio::test]
+async fn question_to_markdown() {
+    use zhihu_link::{ZhihuClient, ZhihuQuestion};
+    let server = mock_server(|server, path| async move {
+        let body = if path == "/questions/7" {
+            r#"{"title":"如何评价 Rust？","detail":"<p>问题描述</p>"}"#.to_string()
+        }
+        else if path.contains("cursor=c2") {
+            r#"{"data":[{"target":{"type":"answer","id":3,"voteup_count":50,"author":{"name":"张三"},"content":"<p>第三个回答</p>"}}],"paging":{"is_end":true}}"#.to_string()
+        }
+        else {
+            format!(
+                r#"{{"data":[{{"target":{{"type":"answer","id":1,"voteup_count":10,"author":{{"name":"李四"}},"content":"<p>第一个回答</p>"}}}},{{"target":{{"type":"answer","id":2,"voteup_count":1,"author":{{"name":""}},"content":"<p>水</p>"}}}}],"paging":{{"is_end":false,"next":"{server}/questions/7/feeds?limit=20&cursor=c2"}}}}"#
+            )
+        };
+        Some((200, body))
+    })
+    .await;
+    let markdown = ZhihuQuestion::with_client(ZhihuClient::default().api_base(&server), 7)
+        .options(RenderOptions::default().frontmatter(true))
+        .min_votes(5)
+        .sort_by_votes(true)
+        .to_markdown()
+        .await
+        .unwrap();
+    let expected = "---
+title: \"如何评价 Rust？\"
+url: \"https://www.zhihu.com/question/7\"
+answers: 2
+---
+
+# 如何评价 Rust？
+
+问题描述
+
+---
+
+## 张三
+
+> 赞同 50 · <https://www.zhihu.com/question/7/answer/3>
+
+第三个回答
+
+---
+
+## 李四
+
+> 赞同 10 · <https://www.zhihu.com/question/7/answer/1>
+
+第一个回答
+";
+    assert_eq!(markdown, expected);
+}
+
+#[tokio::test]
+async fn resume_question_from_cursor() {
+    use zhihu_link::{ZhihuClient, ZhihuQuestion};
+    let server = mock_server(|server, path| async move {
+        let body = match path.as_str() {
+            "/questions/42/feeds?limit=20" => format!(
+                r#"{{"data":[{{"target":{{"type":"answer","id":1}}}},{{"target":{{"type":"answer","id":2}}}}],"paging":{{"is_end":false,"next":"{server}/questions/42/feeds?limit=20&cursor=c2"}}}}"#
+            ),
+            "/questions/42/feeds?limit=20&cursor=c2" => {
+                r#"{"data":[{"target":{"type":"answer","id":3}}],"paging":{"is_end":true,"next":"ignored"}}"#.to_string()
+            }
+            _ => return None,
+        };
+        Some((200, body))
+    })
+    .await;
+    let question = ZhihuQuestion::with_client(ZhihuClient::default().api_base(&server), 42);
+    let first = question.answers_from_cursor(None).await.unwrap();
+    assert_eq!(first.answers(), [1, 2]);
+    assert_eq!(first.next_cursor(), Some("c2"));
+    // a new crawl resumes from the saved cursor
+    let saved = first.next_cursor().unwrap().to_string();
+    let rest = ZhihuQuestion::with_client(ZhihuClient::default().api_base(&server), 42)
+        .answers_from_cursor(Some(&saved))
+        .await
+        .unwrap();
+    assert_eq!(rest.answers(), [3]);
+    assert_eq!(rest.next_cursor(), None);
+}
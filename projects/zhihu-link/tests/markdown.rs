@@ -0,0 +1,643 @@
+//! Rendering of the content elements to markdown.
+
+mod common;
+
+use common::{answer_page, article_page};
+use std::str::FromStr;
+use zhihu_link::{MathDelimiters, ParseMode, RenderOptions, Selector, Typography, ZhihuAnswer, ZhihuArticle, ZhihuError};
+
+#[test]
+fn smart_quotes() {
+    let options = RenderOptions::default().typography(Typography::Smart);
+    let answer = ZhihuAnswer::from_html(&answer_page(r#"<p>He said "it's fine" -- really</p>"#), options.clone()).unwrap();
+    assert!(answer.to_string().contains("He said “it’s fine” – really"));
+    let spaced = ZhihuAnswer::from_html(&answer_page(r#"<p>Say<span> "yes"</span></p>"#), options).unwrap();
+    assert!(spaced.to_string().contains("Say “yes”"), "{spaced}");
+}
+
+#[test]
+fn inline_and_display_math() {
+    let html = answer_page(
+        r#"<p>令<span class="ztext-math" data-tex="n">n</span>为正整数, 则</p><p><span class="ztext-math" data-tex="\sum_{i=1}^n i = \frac{n(n+1)}{2}">...</span></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("令 $n$ 为正整数"));
+    assert!(answer.contains("\n\n$$\n\\sum_{i=1}^n i = \\frac{n(n+1)}{2}\n$$\n\n"));
+    let brackets = RenderOptions::default().math_delimiters(MathDelimiters::Brackets);
+    let answer = ZhihuAnswer::from_html(&html, brackets).unwrap().to_string();
+    assert!(answer.contains("令 \\(n\\) 为正整数"));
+    assert!(answer.contains("\n\n\\[\n\\sum_{i=1}^n i = \\frac{n(n+1)}{2}\n\\]\n\n"));
+    // the editor ends block formulas with `\\`, even amid text
+    let html = answer_page(r#"<p>由此<span class="ztext-math" data-tex="E = mc^2\\">E</span>可知</p>"#);
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("由此\n$$\nE = mc^2\n$$\n可知"));
+    // punctuation and the end of a line follow an inline formula directly
+    let html = answer_page(
+        r#"<p>取<span class="ztext-math" data-tex="n">n</span>，再取<span class="ztext-math" data-tex="m">m</span></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("取 $n$，再取 $m$\n"), "{answer}");
+    // and stay inside a list item
+    let html = answer_page(r#"<ul><li>x<span class="ztext-math" data-tex="a\\">a</span>y</li></ul>"#);
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("- x\n  $$\n  a\n  $$\n  y\n"), "{answer}");
+}
+
+#[test]
+fn eeimg_formulas() {
+    let html = answer_page(
+        r#"<p>设<img src="https://www.zhihu.com/equation?tex=a%5E2%2Bb%5E2" alt="[公式]" eeimg="1">则</p><p><img src="//www.zhihu.com/equation?tex=x" alt="\int_0^1 f(x)\,dx" eeimg="1" data-formula="\int_0^1 f(x)\,dx"></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("设 $a^2+b^2$ 则"));
+    assert!(answer.contains("$$\n\\int_0^1 f(x)\\,dx\n$$"));
+}
+
+#[test]
+fn hyperlinks() {
+    let html = answer_page(
+        r##"<p>见<a href="https://link.zhihu.com/?target=https%3A//github.com/oovm/zhihu-markdown" class=" external" target="_blank" rel="nofollow noreferrer"><span class="invisible">https://</span><span class="visible">github.com/oovm</span></a></p><p><a href="#ref_1">[1]</a><a href="https://example.com"></a></p>"##,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("见[https://github.com/oovm](https://github.com/oovm/zhihu-markdown)"));
+    assert!(answer.contains("[1]<https://example.com>"));
+}
+
+#[test]
+fn video_thumbnails() {
+    let html = answer_page(
+        r#"<p><a href="https://www.youtube.com/watch?v=dQw4w9WgXcQ&amp;t=42">演示视频</a> <a href="https://youtu.be/abc_-123">短链</a> <a href="https://www.bilibili.com/video/BV1xx411c7mD">B站</a></p>"#,
+    );
+    let plain = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(plain.contains("[演示视频](https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42)"));
+    let options = RenderOptions::default().video_thumbnails(true);
+    let answer = ZhihuAnswer::from_html(&html, options).unwrap().to_string();
+    assert!(answer.contains(
+        "[![演示视频](https://img.youtube.com/vi/dQw4w9WgXcQ/hqdefault.jpg)](https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42)"
+    ));
+    assert!(answer.contains("[![短链](https://img.youtube.com/vi/abc_-123/hqdefault.jpg)](https://youtu.be/abc_-123)"));
+    assert!(answer.contains(" [B站](https://www.bilibili.com/video/BV1xx411c7mD)"));
+}
+
+#[test]
+fn tracking_anchors() {
+    let html = answer_page(
+        r#"<p>参见<a class="internal" href="//www.zhihu.com/question/1?utm_source=zhihu&amp;utm_medium=social&amp;page=2" data-za-detail-view-element_name="Title" data-za-extra-module='{"card":{"content":{"type":"Question"}}}' data-za-detail-view-id="1043">这个问题</a>和<a href="https://link.zhihu.com/?target=https%3A//example.com/%3Futm_campaign%3Dx" data-za-detail-view-element_name="Link">外链</a></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("参见[这个问题](https://www.zhihu.com/question/1?page=2)和[外链](https://example.com/)"));
+}
+
+#[test]
+fn mathml_formulas() {
+    let html = answer_page(
+        r#"<p>勾股<span class="ztext-math"><math><msup><mi>a</mi><mn>2</mn></msup><mo>+</mo><msup><mi>b</mi><mn>2</mn></msup><mo>=</mo><msqrt><mi>c</mi></msqrt></math></span>定理</p><p><span class="ztext-math"><math display="block"><semantics><mfrac><mn>1</mn><mi>π</mi></mfrac><annotation encoding="application/x-tex">\frac{1}{\pi}</annotation></semantics></math></span></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("勾股 ${a}^{2}+{b}^{2}=\\sqrt{c}$ 定理"));
+    assert!(answer.contains("$$\n\\frac{1}{\\pi}\n$$"));
+    let raw = ZhihuAnswer::from_html(&html, RenderOptions::default().raw_mathml(true)).unwrap().to_string();
+    assert!(raw.contains("勾股<math><msup><mi>a</mi><mn>2</mn></msup>"));
+    // braces are escaped as fences and a missing fence is invisible
+    let html = answer_page(
+        r#"<p>设<span class="ztext-math"><math><mi>f</mi><mo>=</mo><mfenced open="{" close=""><mi>x</mi></mfenced></math></span></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("设 $f=\\left\\{x\\right.$"), "{answer}");
+    // function names are upright commands rather than a product of variables
+    let html = answer_page(
+        r#"<p>即<span class="ztext-math"><math><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi><mo>+</mo><mi>ab</mi></math></span></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("即 $\\sin x+\\mathrm{ab}$"), "{answer}");
+}
+
+#[test]
+fn picture_sources() {
+    let html = answer_page(
+        r#"<figure><picture><source type="image/avif" srcset="https://pic1.zhimg.com/a.avif"><source srcset="https://pic1.zhimg.com/a.webp 1x, https://pic1.zhimg.com/a@2x.webp 2x"><source type="image/jpeg" srcset="https://pic1.zhimg.com/a.jpg 720w, https://pic1.zhimg.com/a_r.jpg 1440w"><img src="https://pic1.zhimg.com/a_fallback.png" alt="配图"></picture></figure><p><picture><source type="image/webp" srcset="https://pic1.zhimg.com/b.webp"><img src="https://pic1.zhimg.com/b.png"></picture></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    let markdown = answer.to_string();
+    assert!(markdown.contains("![配图](https://pic1.zhimg.com/a_r.jpg)\n\n![](https://pic1.zhimg.com/b.png)\n\n"));
+    assert!(answer.unhandled_tags().is_empty());
+    let modern = ZhihuAnswer::from_html(&html, RenderOptions::default().modern_images(true)).unwrap().to_string();
+    assert!(modern.contains("![配图](https://pic1.zhimg.com/a.avif)\n\n![](https://pic1.zhimg.com/b.webp)\n\n"));
+}
+
+#[test]
+fn line_break_runs() {
+    let html = answer_page("<p>第一行<br>第二行<br><br> <br>第二段<br></p><ul><li>项目<br><br>续行</li></ul>");
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default()).unwrap();
+    let text = answer.to_string();
+    assert!(text.contains("第一行\\\n第二行\n\n第二段\n\n"), "{text}");
+    assert!(text.contains("- 项目\\\n  续行"), "{text}");
+}
+
+#[test]
+fn inline_styles() {
+    let html = answer_page(
+        r#"<p>普通<span style="font-weight:bold">加粗 </span>文字<span style="font-weight: 700"><span style="color:#e33; font-style: italic">红色斜体<span style="font-weight:bold">内层</span></span></span></p>"#,
+    );
+    let styled = ZhihuAnswer::from_html(&html, RenderOptions::default().inline_styles(true)).unwrap().to_string();
+    // the color tag is punctuation to the markers, which would not open after a letter
+    assert!(
+        styled.contains("普通**加粗** 文字<strong><em><span style=\"color: #e33\">红色斜体内层</span></em></strong>"),
+        "{styled}"
+    );
+    let plain = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(plain.contains("普通加粗 文字红色斜体内层"), "{plain}");
+}
+
+#[test]
+fn question_description() {
+    let html = r#"<html><body><h1 class="QuestionHeader-title">标题</h1><div class="QuestionRichText"><span><p>问题的<code>背景</code></p><p>第二段</p></span></div><span class="CopyrightRichText-richText"><p>回答</p></span></body></html>"#;
+    let answer = ZhihuAnswer::from_html(html, RenderOptions::default().question_description(true)).unwrap();
+    assert_eq!(answer.description(), "问题的`背景`\n\n第二段");
+    assert!(answer.to_string().starts_with("# 标题\n\n> 问题的`背景`\n>\n> 第二段\n\n回答"), "{answer}");
+    // left out unless asked for
+    assert!(ZhihuAnswer::from_str(html).unwrap().to_string().starts_with("# 标题\n\n回答"));
+}
+
+#[test]
+fn question_description_collected() {
+    let html = r#"<html><body><h1 class="QuestionHeader-title">标题</h1><div class="QuestionRichText"><p>见<sup data-text="书" data-url="https://example.com/book" data-draft-type="reference" data-numero="1">[1]</sup><blink>闪</blink></p></div><span class="CopyrightRichText-richText"><p>回答</p></span></body></html>"#;
+    let options = RenderOptions::default().mode(ParseMode::Strict).question_description(true).references(true);
+    // an unknown tag in the question is reported, not an error of the answer
+    let answer = ZhihuAnswer::from_html(html, options).unwrap();
+    assert_eq!(answer.unhandled_tags(), ["blink"]);
+    assert!(answer.to_string().contains("https://example.com/book"), "{answer}");
+}
+
+#[test]
+fn pins() {
+    use zhihu_link::{ContentKind, UrlDispatcher, ZhihuPin};
+    let url = "https://www.zhihu.com/pin/1630000000000000000";
+    assert_eq!(UrlDispatcher::classify(url), Some(ContentKind::Pin { id: 1630000000000000000 }));
+    let html = format!(
+        r#"<html><head><link rel="canonical" href="{url}"></head><body><div class="AuthorInfo"><meta itemprop="name" content="阿斯特"></div><div class="PinItem-content"><div class="RichText"><p>今天的<a href="https://example.com">晚霞</a></p></div><div class="Pin-images"><img src="https://pic1.zhimg.com/a.jpg"><img src="https://pic1.zhimg.com/b.jpg"></div></div></body></html>"#
+    );
+    let pin = ZhihuPin::from_str(&html).unwrap();
+    assert_eq!(pin.permalink(), Some(url));
+    assert_eq!(pin.author(), Some("阿斯特"));
+    assert_eq!(pin.images(), ["https://pic1.zhimg.com/a.jpg", "https://pic1.zhimg.com/b.jpg"]);
+    assert_eq!(
+        pin.to_string(),
+        "# 阿斯特 的想法\n\n> 作者: 阿斯特\n\n今天的[晚霞](https://example.com)\n\n![](https://pic1.zhimg.com/a.jpg)\n\n![](https://pic1.zhimg.com/b.jpg)\n\n"
+    );
+}
+
+#[test]
+fn lone_image_paragraphs() {
+    let html = answer_page(
+        r#"<p>前文<img src="https://pic1.zhimg.com/inline.png" alt="图标">后文</p><p> <img src="https://pic1.zhimg.com/block.jpg" alt="示意"> <br></p><p>结尾</p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    let markdown = answer.to_string();
+    assert!(markdown.contains("前文![图标](https://pic1.zhimg.com/inline.png)后文\n\n"));
+    assert!(markdown.contains("\n\n![示意](https://pic1.zhimg.com/block.jpg)\n\n结尾"));
+    assert!(answer.unhandled_tags().is_empty());
+}
+
+#[test]
+fn update_sections() {
+    let html = answer_page(
+        r#"<p>原来的回答。</p><hr><p><b>补充说明：</b></p><p>补充的内容。</p><p>更新 2023.5.1</p><p>更新的内容，补充一点。</p>"#,
+    );
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().update_sections(true)).unwrap().to_string();
+    assert!(answer.contains(
+        "原来的回答。\n\n---\n\n#### 补充说明\n\n补充的内容。\n\n---\n\n#### 更新 2023.5.1\n\n更新的内容，补充一点。"
+    ));
+    // left as prose unless asked for
+    let plain = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(plain.contains("原来的回答。\n\n---\n\n**补充说明：**\n\n补充的内容。\n\n更新 2023.5.1\n\n"), "{plain}");
+}
+
+#[test]
+fn link_cards() {
+    let html = answer_page(
+        r#"<p><span class="RichText-LinkCardContainer"><a target="_blank" href="https://link.zhihu.com/?target=https%3A//www.rust-lang.org/" data-draft-type="link-card" class="LinkCard new"><span class="LinkCard-contents"><span class="LinkCard-title loading" data-text="true">Rust Programming Language</span><span class="LinkCard-desc"><span><svg viewBox="0 0 24 24"><path d="M0"></path></svg></span>www.rust-lang.org</span></span><span class="LinkCard-image"><img src="https://pic1.zhimg.com/card.jpg"></span></a></span></p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    assert_eq!(answer.to_string(), "# 标题\n\n[Rust Programming Language](https://www.rust-lang.org/)\n\n");
+    assert!(answer.unhandled_tags().is_empty());
+    let thumbnail = ZhihuAnswer::from_html(&html, RenderOptions::default().link_card_images(true)).unwrap();
+    assert!(
+        thumbnail
+            .to_string()
+            .contains("[![Rust Programming Language](https://pic1.zhimg.com/card.jpg)](https://www.rust-lang.org/)")
+    );
+    // older answers have the card without its container, titled by the data attributes
+    let bare = answer_page(
+        r#"<p><a href="https://link.zhihu.com/?target=https%3A//crates.io/" data-draft-type="link-card" data-text="crates.io" data-image="https://pic2.zhimg.com/crates.jpg">https://crates.io/</a></p>"#,
+    );
+    assert!(ZhihuAnswer::from_str(&bare).unwrap().to_string().contains("[crates.io](https://crates.io/)"));
+    let bare = ZhihuAnswer::from_html(&bare, RenderOptions::default().link_card_images(true)).unwrap().to_string();
+    assert!(bare.contains("[![crates.io](https://pic2.zhimg.com/crates.jpg)](https://crates.io/)"));
+}
+
+#[test]
+fn blockquotes() {
+    let html = answer_page(
+        r#"<p>前言</p><blockquote><p>引用</p><p>第二段</p><blockquote>嵌套</blockquote></blockquote><ul><li>条目<blockquote>列表中的引用</blockquote></li></ul><p>结束</p>"#,
+    );
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().mode(ParseMode::Strict)).unwrap().to_string();
+    assert!(answer.contains("前言\n\n> 引用\n>\n> 第二段\n>\n> > 嵌套\n\n- 条目\n  > 列表中的引用\n\n结束"));
+}
+
+#[test]
+fn list_numbering() {
+    let html = answer_page(
+        r#"<ol><li>一</li><li value="5">五</li><li>六</li></ol><ol reversed><li>三</li><li>二</li><li>一</li></ol><ol reversed start="10"><li>十</li><li>九</li></ol>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("1. 一\n5. 五\n6. 六\n"));
+    assert!(answer.contains("3. 三\n2. 二\n1. 一\n"));
+    assert!(answer.contains("10. 十\n9. 九\n"));
+}
+
+#[test]
+fn nested_lists() {
+    let html = answer_page(
+        r#"<p>水果:</p><ul><li>苹果<ul><li>红富士</li><li>含<span class="ztext-math" data-tex="x">x</span></li></ul></li><li><a href="https://example.com">香蕉</a></li></ul><ol><li>一</li><li>二</li></ol><p>结束</p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("水果:\n\n- 苹果\n  - 红富士\n  - 含 $x$\n- [香蕉](https://example.com)\n\n1. 一\n2. 二\n\n结束"));
+}
+
+#[test]
+fn paragraphs_in_list_items() {
+    let html = answer_page(
+        r#"<ul><li><p>text</p></li><li><p>第一段</p><p>第二段</p></li></ul><ol><li><p>一</p><ul><li><p>子项</p></li></ul></li></ol><p>结束</p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("- text\n- 第一段\n  第二段\n\n1. 一\n   - 子项\n\n结束"));
+    let loose = ZhihuAnswer::from_html(&html, RenderOptions::default().loose_lists(true)).unwrap().to_string();
+    assert!(loose.contains("- text\n- 第一段\n\n  第二段\n"));
+}
+
+#[test]
+fn mixed_nested_lists() {
+    let html = answer_page(
+        r#"<ul><li>步骤<ol><li>准备</li><li>执行<ul><li>细节</li></ul></li></ol></li><li>总结</li></ul><ol start="9"><li>九<ul><li>子项</li></ul></li><li>十<ol><li>甲</li></ol></li></ol>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("- 步骤\n  1. 准备\n  2. 执行\n     - 细节\n- 总结\n\n"));
+    assert!(answer.contains("9. 九\n   - 子项\n10. 十\n    1. 甲\n"));
+    // lists put right into a list nest under the item before them
+    let html = answer_page(r#"<ol><li>一</li><ul><li>子项</li><ol><li>孙项</li></ol></ul><li>二</li></ol><p>结束</p>"#);
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("1. 一\n   - 子项\n     1. 孙项\n2. 二\n\n结束"));
+}
+
+#[test]
+fn remove_selectors() {
+    let html = answer_page(
+        r#"<p>正文</p><div class="RichContent-actions"><button>赞同 12</button><a href="https://example.com">相关推荐</a></div>"#,
+    );
+    let options = RenderOptions::default().remove_selectors(vec![Selector::new(".RichContent-actions")]);
+    let answer = ZhihuAnswer::from_html(&html, options).unwrap();
+    assert_eq!(answer.to_string(), "# 标题\n\n正文\n\n");
+    assert!(answer.unhandled_tags().is_empty());
+}
+
+#[test]
+fn reference_section() {
+    let html = article_page(
+        r#"<p>见文献<sup data-text="Rust 程序设计语言" data-url="https://doc.rust-lang.org/book/" data-draft-node="inline" data-draft-type="reference" data-numero="1">[1]</sup>与<sup data-text="" data-url="https://example.com" data-draft-type="reference" data-numero="2">[2]</sup>, 再见<sup data-text="Rust 程序设计语言" data-url="https://doc.rust-lang.org/book/" data-draft-type="reference" data-numero="1">[1]</sup></p>"#,
+    );
+    let plain = ZhihuArticle::from_str(&html).unwrap().to_string();
+    assert_eq!(plain, "# 专栏标题\n\n见文献[1]与[2], 再见[1]\n\n");
+    let article = ZhihuArticle::from_html(&html, RenderOptions::default().references(true)).unwrap().to_string();
+    assert_eq!(
+        article,
+        "# 专栏标题\n\n见文献[1]与[2], 再见[1]\n\n## 参考\n\n1. [Rust 程序设计语言](https://doc.rust-lang.org/book/)\n2. <https://example.com>\n"
+    );
+    let footnotes = ZhihuArticle::from_html(&html, RenderOptions::default().footnotes(true)).unwrap().to_string();
+    assert_eq!(
+        footnotes,
+        "# 专栏标题\n\n见文献[^1]与[^2], 再见[^1]\n\n[^1]: [Rust 程序设计语言](https://doc.rust-lang.org/book/)\n[^2]: <https://example.com>\n"
+    );
+}
+
+#[test]
+fn related_appendix() {
+    let html = format!(
+        "{}{}",
+        answer_page("<p>正文</p>"),
+        r#"<div class="Card"><div class="SimilarQuestions-item"><a href="/question/1">问题一</a></div><div class="SimilarQuestions-item"><a href="//zhuanlan.zhihu.com/p/2">文章二</a></div></div>"#
+    );
+    let plain = ZhihuAnswer::from_str(&html).unwrap();
+    assert_eq!(plain.related().len(), 2);
+    assert!(!plain.to_string().contains("相关推荐"));
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().related(true)).unwrap();
+    assert_eq!(
+        answer.to_string(),
+        "# 标题\n\n正文\n\n## 相关推荐\n\n- [问题一](https://www.zhihu.com/question/1)\n- [文章二](https://zhuanlan.zhihu.com/p/2)\n"
+    );
+}
+
+#[test]
+fn post_process_hook() {
+    let html = answer_page("<p>使用 rust 编写</p>");
+    let options = RenderOptions::default().citation(true).post_process(|md| md.replace("rust", "RUST"));
+    assert_eq!(ZhihuAnswer::from_html(&html, options).unwrap().to_string(), "# 标题\n\n使用 RUST 编写\n\n");
+    assert!(ZhihuAnswer::from_str(&html).unwrap().to_string().contains("使用 rust 编写"));
+}
+
+#[test]
+fn gif_players() {
+    let html = answer_page(
+        r#"<div class="RichText-gifPlaceholder"><div class="GifPlayer" data-size="normal"><img class="ztext-gif GifPlayer-gif2mp4Image" src="https://pic1.zhimg.com/v2-abc_b.jpg" data-thumbnail="https://pic1.zhimg.com/v2-abc_b.jpg"><svg class="GifPlayer-icon"></svg></div></div><figure><img class="ztext-gif" data-original="https://pic2.zhimg.com/v2-def_r.gif" src="https://pic2.zhimg.com/v2-def_b.jpg"><figcaption>动图</figcaption></figure>"#,
+    );
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().mode(ParseMode::Strict)).unwrap();
+    assert!(
+        answer
+            .to_string()
+            .contains("![](https://pic1.zhimg.com/v2-abc_b.gif)\n\n![动图](https://pic2.zhimg.com/v2-def_r.gif)\n")
+    );
+}
+
+#[test]
+fn gif_videos() {
+    let html = answer_page(
+        r#"<p>看图</p><figure><video loop muted autoplay data-poster="https://pic1.zhimg.com/v2-a.jpg" src="https://vdn.vzuu.com/a.mp4"></video></figure><video controls poster="https://pic1.zhimg.com/v2-b.jpg"><source src="https://vdn.vzuu.com/b.mp4"></video>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("看图\n\n[![动图](https://pic1.zhimg.com/v2-a.jpg)](https://vdn.vzuu.com/a.mp4)\n\n"));
+    assert!(answer.contains("[![视频](https://pic1.zhimg.com/v2-b.jpg)](https://vdn.vzuu.com/b.mp4)"));
+}
+
+#[test]
+fn image_titles() {
+    let html = answer_page(
+        r#"<figure><img alt="曲线" data-original="https://pic1.zhimg.com/v2-a.jpg"><figcaption>图 1 "收敛"曲线</figcaption></figure><figure><img alt="表格" title="表格" src="https://pic1.zhimg.com/v2-b.jpg"></figure>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains(r#"![曲线](https://pic1.zhimg.com/v2-a.jpg "图 1 \"收敛\"曲线")"#));
+    assert!(answer.contains("![表格](https://pic1.zhimg.com/v2-b.jpg)\n"));
+    let lines = ZhihuAnswer::from_html(&html, RenderOptions::default().caption_lines(true)).unwrap().to_string();
+    assert!(lines.contains("![曲线](https://pic1.zhimg.com/v2-a.jpg)\n\n*图 1 \"收敛\"曲线*\n\n![表格]"));
+    // the caption is only written beneath the image, escaped
+    let html = answer_page(r#"<figure><img src="https://pic1.zhimg.com/a.jpg"><figcaption>说明*</figcaption></figure>"#);
+    let lines = ZhihuAnswer::from_html(&html, RenderOptions::default().caption_lines(true)).unwrap().to_string();
+    assert!(lines.contains("![](https://pic1.zhimg.com/a.jpg)\n\n*说明\\**\n"));
+}
+
+#[test]
+fn sentence_split_across_spans() {
+    let html = answer_page(
+        "<p><span>Rust is</span> <span>a systems </span><span>language</span>, <code>unsafe</code> <b>included</b> </p>\n<p>下一段</p>",
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("Rust is a systems language, `unsafe` **included**\n\n下一段\n\n"));
+}
+
+#[test]
+fn horizontal_rules() {
+    let html = answer_page(r#"<p>上文</p><hr><hr><p>下文</p><ol><li>一<hr><hr>续</li><li>二</li></ol><p>结束</p>"#);
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().mode(ParseMode::Strict)).unwrap().to_string();
+    assert!(answer.contains("上文\n\n---\n\n下文\n\n1. 一\n\n   ---\n\n   续\n2. 二\n\n结束"));
+}
+
+#[test]
+fn inline_code_backticks() {
+    let html =
+        answer_page(r#"<p>用<code>a`b</code>和<code>``</code>还有<code>`x`</code>以及<code> a </code><code></code></p>"#);
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("用``a`b``和``` `` ```还有`` `x` ``以及`  a  `\n"));
+}
+
+#[test]
+fn emphasis_elements() {
+    let html = answer_page(
+        r#"<p>这是<b> 加粗 </b>和<em>斜体</em>, <strong>外层<i>内层<b>重复</b></i></strong>, <del>删除</del> <s><span style="text-decoration: line-through">一次</span></s><b> </b>结束</p>"#,
+    );
+    let options = RenderOptions::default().mode(ParseMode::Strict).inline_styles(true);
+    let answer = ZhihuAnswer::from_html(&html, options).unwrap();
+    // emphasis opened by an enclosing element is not repeated
+    assert_eq!(answer.to_string(), "# 标题\n\n这是 **加粗** 和*斜体*, **外层*内层重复***, ~~删除~~ ~~一次~~ 结束\n\n");
+}
+
+#[test]
+fn emphasis_beside_punctuation() {
+    // markers between a letter and punctuation are not emphasis, html tags are
+    let html = answer_page(r#"<p>中<b>“强调”</b>文, <em>“引号”</em>。 <b>“前后”</b> 有空格</p>"#);
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("中<strong>“强调”</strong>文, *“引号”*。 **“前后”** 有空格"), "{answer}");
+    // emphasis does not span paragraphs
+    let blocks = ZhihuAnswer::from_str(&answer_page("<b><p>one</p><p>two</p></b>")).unwrap().to_string();
+    assert!(blocks.contains("**one**\n\n**two**"), "{blocks}");
+}
+
+#[test]
+fn srcset_largest_candidate() {
+    let html = answer_page(
+        r#"<figure><img loading="lazy" src="data:image/svg+xml;utf8,&lt;svg&gt;" srcset="https://pic1.zhimg.com/v2-a_720w.jpg 720w, https://pic1.zhimg.com/v2-a_1440w.jpg 1440w, https://pic1.zhimg.com/v2-a_r.jpg 1080w"></figure><figure><img src="https://pic1.zhimg.com/b.jpg" srcset="https://pic1.zhimg.com/b.jpg, https://pic1.zhimg.com/b@2x.jpg 2x"></figure>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("![](https://pic1.zhimg.com/v2-a_1440w.jpg)"));
+    assert!(answer.contains("![](https://pic1.zhimg.com/b@2x.jpg)"));
+}
+
+#[test]
+fn numbered_alt_fallback() {
+    let html = answer_page(
+        r#"<figure><img src="https://pic1.zhimg.com/a.jpg"></figure><figure><img src="https://pic1.zhimg.com/b.jpg"><figcaption>说明</figcaption></figure><figure><img src="https://pic1.zhimg.com/c.jpg"></figure>"#,
+    );
+    let plain = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(plain.contains("![](https://pic1.zhimg.com/a.jpg)"));
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().numbered_alt("图")).unwrap().to_string();
+    assert!(answer.contains(
+        "![图1](https://pic1.zhimg.com/a.jpg)\n\n![说明](https://pic1.zhimg.com/b.jpg)\n\n![图3](https://pic1.zhimg.com/c.jpg)"
+    ));
+}
+
+#[test]
+fn video_cards() {
+    let html = answer_page(
+        r#"<p>前言</p><a class="video-box" href="https://link.zhihu.com/?target=https%3A//www.zhihu.com/video/123" target="_blank" data-video-id="" data-name="演示" data-poster="https://pic1.zhimg.com/cover.jpg" data-lens-id="123"><img class="thumbnail" src="https://pic1.zhimg.com/cover.jpg"><span class="content"><span class="title">演示<span class="z-ico-extern-gray"></span></span><span class="url"><span class="z-ico-video"></span>https://www.zhihu.com/video/123</span></span></a><div class="RichText-video" data-za-extra-module='{"card":{"content":{"type":"Video","video_id":"456"}}}'><div class="VideoCard"><img src="https://pic1.zhimg.com/new.jpg"></div></div>"#,
+    );
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().mode(ParseMode::Strict)).unwrap();
+    assert_eq!(
+        answer.to_string(),
+        "# 标题\n\n前言\n\n[![演示](https://pic1.zhimg.com/cover.jpg)](https://www.zhihu.com/video/123)\n\n[![视频](https://pic1.zhimg.com/new.jpg)](https://www.zhihu.com/video/456)\n\n"
+    );
+}
+
+#[test]
+fn strict_and_lenient_mode() {
+    let html = answer_page(r#"<p>前言</p><div class="Unknown"><span>未知</span></div>"#);
+    let lenient = ZhihuAnswer::from_html(&html, RenderOptions::default()).unwrap();
+    assert!(lenient.to_string().contains("前言\n\n未知"));
+    let strict = ZhihuAnswer::from_html(&html, RenderOptions::default().mode(ParseMode::Strict));
+    assert!(matches!(strict, Err(ZhihuError::UnknownElement(tag)) if tag == "div"));
+    let strict = ZhihuAnswer::from_html("<html><body></body></html>", RenderOptions::default().mode(ParseMode::Strict));
+    assert!(matches!(strict, Err(ZhihuError::MissingContent(_))));
+}
+
+#[test]
+fn heading_offset() {
+    let html = article_page(r#"<h1>大标题</h1><h2>第一节</h2><h3>小节</h3><h6>最深</h6>"#);
+    let plain = ZhihuArticle::from_str(&html).unwrap().to_string();
+    assert!(plain.contains("# 大标题\n\n## 第一节\n\n### 小节\n\n###### 最深\n"));
+    let article = ZhihuArticle::from_html(&html, RenderOptions::default().heading_offset(1)).unwrap().to_string();
+    assert!(article.contains("# 专栏标题\n\n## 大标题\n\n### 第一节\n\n#### 小节\n\n###### 最深\n"));
+    let answer = ZhihuAnswer::from_html(&answer_page("<h2>一节</h2>"), RenderOptions::default().heading_offset(2));
+    assert!(answer.unwrap().to_string().contains("#### 一节\n"));
+}
+
+#[test]
+fn render_one_section() {
+    let html =
+        article_page(r#"<p>导言</p><h2 id="a">第一节</h2><p>甲</p><h3>小节</h3><p>乙</p><h2 id="b">第二节</h2><p>丙</p>"#);
+    let article = ZhihuArticle::from_str(&html).unwrap();
+    let section = article.render_section(&Selector::new("h2#a")).unwrap();
+    assert_eq!(section.as_deref(), Some("## 第一节\n\n甲\n\n### 小节\n\n乙\n\n"));
+    assert_eq!(article.render_section(&Selector::new("h2#b")).unwrap().as_deref(), Some("## 第二节\n\n丙\n\n"));
+    assert_eq!(article.render_section(&Selector::new("h4")).unwrap(), None);
+}
+
+#[test]
+fn code_blocks() {
+    let html = answer_page(
+        "<p>调用<code>a_b*c</code>即可</p><pre lang=\"python\"><code>def f(*args, **kw):\n    return $x_1\n\n\nprint(f())\n</code></pre><p>完</p>",
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("调用`a_b*c`即可"));
+    assert!(answer.contains("\n\n```python\ndef f(*args, **kw):\n    return $x_1\n\n\nprint(f())\n```\n\n完"));
+}
+
+#[test]
+fn guessed_code_language() {
+    let html = answer_page(
+        "<pre><code>fn main() {\n    let mut v = Vec::new();\n    println!(\"{:?}\", v);\n}</code></pre><pre><code>x = 1</code></pre><pre lang=\"text\"><code>fn f() -> u8</code></pre>",
+    );
+    let plain = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(plain.contains("```\nfn main()"));
+    let guessed = ZhihuAnswer::from_html(&html, RenderOptions::default().guess_language(true)).unwrap().to_string();
+    assert!(guessed.contains("```rust\nfn main()"));
+    assert!(guessed.contains("```\nx = 1\n```"));
+    assert!(guessed.contains("```text\nfn f() -> u8\n```"));
+}
+
+#[test]
+fn entities_in_prose_and_code() {
+    let html = answer_page(
+        "<p>泛型 &lt;T&gt; 与 a &amp; b, 实体 &amp;lt; 和 <code>Vec&lt;T&gt; &amp;amp;</code></p><pre><code>if a &lt; b &amp;&amp; c &gt; d { &amp;lt; }</code></pre>",
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("泛型 \\<T> 与 a & b, 实体 \\&lt; 和 `Vec<T> &amp;`"));
+    assert!(answer.contains("```\nif a < b && c > d { &lt; }\n```"));
+    let raw = ZhihuAnswer::from_html(&html, RenderOptions::default().raw_html(true)).unwrap().to_string();
+    assert!(raw.contains("泛型 <T> 与 a & b, 实体 &lt; 和 `Vec<T> &amp;`"));
+}
+
+#[test]
+fn code_block_indentation() {
+    let html = answer_page(
+        "<div class=\"highlight\"><pre><code class=\"language-python\">    def f(x):\n\t    <span class=\"k\">return</span> x</code></pre></div><pre><code>if a:<br>\u{a0}\u{a0}\u{a0}\u{a0}pass<br>  </code></pre>",
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("```python\n    def f(x):\n\t    return x\n```\n\n"));
+    assert!(answer.contains("```\nif a:\n    pass\n  \n```\n\n"));
+    // zhihu's language names become common info strings
+    let html = answer_page(
+        r#"<div class="highlight"><pre lang="C++"><code>int x;</code></pre></div><div class="highlight"><pre><code class="language-plaintext">纯文本</code></pre></div>"#,
+    );
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().mode(ParseMode::Strict)).unwrap().to_string();
+    assert!(answer.contains("```cpp\nint x;\n```\n\n```text\n纯文本\n```\n"));
+}
+
+#[test]
+fn unhandled_tags() {
+    let html = answer_page("<p>术语:</p><dl><dt>甲</dt><dd>乙</dd></dl>");
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    assert!(answer.to_string().contains("甲乙"));
+    assert_eq!(answer.unhandled_tags(), ["dl", "dt", "dd"]);
+}
+
+#[test]
+fn table_alignment() {
+    let html = answer_page(
+        r#"<p>表格:</p><table><thead><tr><th>名称</th><th align="center">数量</th><th style="text-align: right">价格</th></tr></thead><tbody><tr><td><code>苹果</code></td><td align="center">3</td><td>5|元</td></tr><tr><td>梨</td><td>1</td></tr></tbody></table><p>完</p>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    assert!(
+        answer
+            .to_string()
+            .contains("表格:\n\n| 名称 | 数量 | 价格 |\n| --- | :---: | ---: |\n| `苹果` | 3 | 5\\|元 |\n| 梨 | 1 |  |\n\n完")
+    );
+    assert!(answer.unhandled_tags().is_empty());
+}
+
+#[test]
+fn table_spans() {
+    let html = answer_page(
+        r#"<table><tr><th colspan="2">合并</th><th>丙</th></tr><tr><td rowspan="2">甲</td><td>一</td><td>二</td></tr><tr><td>三</td><td>四</td></tr><tr><td>五</td><td colspan="x">六</td></tr></table>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(answer.contains("| 合并 |  | 丙 |\n| --- | --- | --- |\n| 甲 | 一 | 二 |\n|  | 三 | 四 |\n| 五 | 六 |  |\n"));
+}
+
+#[test]
+fn bilibili_image_suffix() {
+    use zhihu_link::utils::normalize_bilibili_image;
+    assert_eq!(
+        normalize_bilibili_image("//i0.hdslb.com/bfs/article/a1b2.png@1256w_708h.webp"),
+        "//i0.hdslb.com/bfs/article/a1b2.png"
+    );
+    assert_eq!(
+        normalize_bilibili_image("https://i0.hdslb.com/bfs/article/a1b2.png"),
+        "https://i0.hdslb.com/bfs/article/a1b2.png"
+    );
+    assert_eq!(normalize_bilibili_image("https://pic1.zhimg.com/v2-1@2x.jpg"), "https://pic1.zhimg.com/v2-1@2x.jpg");
+}
+
+#[test]
+fn strip_markdown_to_text() {
+    use zhihu_link::utils::strip_markdown;
+    let markdown = "---\ntitle: \"标题\"\n---\n\n# 标题\n\n> 作者: 匿名用户\n\n见[官网](https://www.rust-lang.org/ \"Rust\")与<https://example.com>, [![动图](https://a.jpg)](https://a.mp4)\n\n*强调*, **粗体**, ~~删除~~, _下划线_, snake_case, \\*星号\\*\n\n- 外层\n  1. 内层 `a*b`\n\n````rust\nlet x = **y;\n````\n\n---\n\n$$\nx^2\n$$\n\n公式 $n$ 完";
+    assert_eq!(
+        strip_markdown(markdown),
+        "标题\n\n作者: 匿名用户\n\n见官网与https://example.com, 动图\n\n强调, 粗体, 删除, 下划线, snake_case, *星号*\n\n外层\n内层 a*b\n\nlet x = **y;\n\nx^2\n\n公式 n 完"
+    );
+}
+
+#[test]
+fn encoding_errors() {
+    use zhihu_link::utils::decode_html;
+    let page = b"<html><head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=gbk\"></head><p>\xb1\xe0\xc2\xeb\x81\x20</p>";
+    match decode_html(page, None) {
+        Err(ZhihuError::Encoding { charset, offset }) => {
+            assert_eq!(charset, "GBK");
+            assert_eq!(offset, page.iter().position(|b| *b == 0x81).unwrap());
+        }
+        other => panic!("expected an encoding error, got {other:?}"),
+    }
+    assert_eq!(decode_html(b"\xef\xbb\xbf<p>\xe7\x9f\xa5</p>", Some("text/html")).unwrap(), "<p>知</p>");
+}
+
+#[test]
+fn trailing_boilerplate() {
+    use zhihu_link::Regex;
+    let html = answer_page("<p>正文。</p><p>欢迎关注我的专栏！</p><p></p><p>觉得有用就点个赞吧</p>");
+    let untouched = ZhihuAnswer::from_str(&html).unwrap().to_string();
+    assert!(untouched.contains("点个赞"));
+    let patterns = vec![Regex::new("^欢迎关注").unwrap(), Regex::new("点个?赞").unwrap()];
+    let options = RenderOptions::default().trailing_boilerplate(patterns);
+    let answer = ZhihuAnswer::from_html(&html.replace("正文。", "正文，欢迎关注。"), options).unwrap().to_string();
+    assert!(answer.ends_with("# 标题\n\n正文，欢迎关注。\n\n"));
+    assert!(!answer.contains("点个赞"));
+}
+
+#[test]
+fn article_shares_answer_renderer() {
+    let html = article_page(r#"<p>令<span class="ztext-math" data-tex="n">n</span>为整数</p><ul><li>一</li></ul>"#);
+    let article = ZhihuArticle::from_html(&html, RenderOptions::default().citation(true)).unwrap();
+    assert_eq!(article.permalink(), Some("https://zhuanlan.zhihu.com/p/620388361"));
+    assert_eq!(
+        article.to_string(),
+        "# 专栏标题\n\n令 $n$ 为整数\n\n- 一\n\n> 原文链接: <https://zhuanlan.zhihu.com/p/620388361>\n"
+    );
+}
@@ -0,0 +1,326 @@
+//! Metadata of the pages: permalinks, authors, counts, times and the frontmatter.
+
+mod common;
+
+use common::{answer_page, article_page};
+use std::str::FromStr;
+use zhihu_link::{Engagement, FrontmatterFormat, ParseMode, RenderOptions, Selector, ZhihuAnswer, ZhihuArticle, ZhihuError};
+
+#[test]
+fn answer_permalink() {
+    let html = r#"<html><head><link rel="canonical" href="https://www.zhihu.com/question/347662352/answer/847873806?utm_id=0"></head>
+<body><h1 class="QuestionHeader-title">标题</h1><span class="CopyrightRichText-richText"><p>正文</p></span></body></html>"#;
+    let answer = ZhihuAnswer::from_html(html, RenderOptions::default().citation(true)).unwrap();
+    assert_eq!(answer.permalink(), Some("https://www.zhihu.com/question/347662352/answer/847873806"));
+    assert!(answer.to_string().ends_with("正文\n\n> 原文链接: <https://www.zhihu.com/question/347662352/answer/847873806>\n"));
+}
+
+#[test]
+fn author_info() {
+    let card = r#"<div class="AuthorInfo"><meta itemprop="name" content="阿斯特"><meta itemprop="image" content="https://pic1.zhimg.com/avatar.jpg"><div class="AuthorInfo-content"><span class="UserLink AuthorInfo-name"><a class="UserLink-link" href="//www.zhihu.com/people/aster">阿斯特</a></span><div class="ztext AuthorInfo-badgeText">Rust 程序员</div></div></div>"#;
+    let html = format!(
+        r#"<html><body><h1 class="Post-Title">专栏标题</h1>{card}<div class="Post-RichText"><p>正文</p></div></body></html>"#
+    );
+    let article = ZhihuArticle::from_str(&html).unwrap();
+    let info = article.author_info().unwrap();
+    assert_eq!(info.name, "阿斯特");
+    assert_eq!(info.bio.as_deref(), Some("Rust 程序员"));
+    assert_eq!(info.avatar.as_deref(), Some("https://pic1.zhimg.com/avatar.jpg"));
+    assert_eq!(info.url.as_deref(), Some("https://www.zhihu.com/people/aster"));
+    // articles only get a byline when asked for
+    assert!(article.to_string().starts_with("# 专栏标题\n\n正文"));
+    let article = ZhihuArticle::from_html(&html, RenderOptions::default().author_byline(true)).unwrap();
+    assert!(
+        article
+            .to_string()
+            .starts_with("# 专栏标题\n\n> 作者: [阿斯特](https://www.zhihu.com/people/aster) · Rust 程序员\n\n正文")
+    );
+    let answer = ZhihuAnswer::from_html(&answer_page(card), RenderOptions::default().author_byline(true)).unwrap();
+    assert_eq!(answer.author_info(), article.author_info());
+}
+
+#[test]
+fn answer_counts() {
+    let html = r#"<h1 class="QuestionHeader-title">标题</h1><span class="CopyrightRichText-richText"><p>正文</p></span><div class="ContentItem-actions"><button>1,024 条评论</button><button>分享</button><button>收藏 1.2 万</button></div>"#;
+    let answer = ZhihuAnswer::from_str(html).unwrap();
+    assert_eq!(answer.favorite_count(), Some(12000));
+    assert_eq!(answer.share_count(), None);
+    assert_eq!(answer.engagement().comments, Some(1024));
+    assert!(!answer.to_string().contains("收藏"));
+}
+
+#[test]
+fn answer_counts_of_this_answer() {
+    // the buttons of the other answers on the page are not counted
+    let html = r#"<html><head><link rel="canonical" href="https://www.zhihu.com/question/1/answer/2"></head><body><h1 class="QuestionHeader-title">标题</h1><div class="ContentItem AnswerItem" name="3"><span class="CopyrightRichText-richText"><p>另一个回答</p></span><div class="ContentItem-actions"><button>赞同 999</button></div></div><div class="ContentItem AnswerItem" name="2"><span class="CopyrightRichText-richText"><p>正文</p></span><div class="ContentItem-actions"><button>12 条评论</button></div></div></body></html>"#;
+    let answer = ZhihuAnswer::from_str(html).unwrap();
+    assert_eq!(answer.engagement(), Engagement { comments: Some(12), ..Engagement::default() });
+    assert!(answer.to_string().starts_with("# 标题\n\n正文"), "{answer}");
+}
+
+#[test]
+fn article_engagement() {
+    let data = r#"{"initialState":{"entities":{"articles":{"1":{"voteupCount":1},"620388361":{"voteupCount":256,"commentCount":32,"favlistsCount":64}}}}}"#;
+    let html = article_page("<p>正文</p>").replace(
+        "</body>",
+        &format!(r#"<div class="ContentItem-actions"><button>赞同 1.5 万</button></div><script id="js-initialData" type="text/json">{data}</script></body>"#),
+    );
+    let article = ZhihuArticle::from_html(&html, RenderOptions::default().frontmatter(true).engagement(true)).unwrap();
+    // the buttons come first, the state of the article fills the rest
+    assert_eq!(article.engagement(), Engagement { votes: Some(15000), comments: Some(32), favorites: Some(64), shares: None });
+    assert!(article.to_string().contains("votes: 15000\ncomment_count: 32\nfavorites: 64\n"));
+    assert!(!ZhihuArticle::from_str(&html).unwrap().to_string().contains("votes"));
+}
+
+#[test]
+fn reading_time() {
+    let paragraph = format!("<p>{}</p>", "知乎是一个问答社区".repeat(50));
+    let answer =
+        ZhihuAnswer::from_html(&answer_page(&paragraph.repeat(3)), RenderOptions::default().frontmatter(true)).unwrap();
+    assert_eq!(answer.reading_minutes(), 4);
+    assert!(answer.to_string().starts_with("---\ntitle: \"标题\"\nreading_minutes: 4\n---\n\n# 标题\n\n"));
+}
+
+#[test]
+fn toml_frontmatter() {
+    let options = RenderOptions::default().frontmatter(true).frontmatter_format(FrontmatterFormat::Toml);
+    let answer = ZhihuArticle::from_html(&article_page("<p>正文</p>"), options).unwrap().to_string();
+    assert!(answer.starts_with(
+        "+++\ntitle = \"专栏标题\"\nreading_minutes = 1\nurl = \"https://zhuanlan.zhihu.com/p/620388361\"\n+++\n\n# 专栏标题\n\n正文"
+    ));
+}
+
+#[test]
+fn frontmatter_metadata() {
+    let html = answer_page("<p>正文</p>").replace(
+        "<body>",
+        r#"<body><div class="QuestionHeader-topics"><div class="Tag">编程</div><div class="Tag">Rust "语言"</div><div class="Tag">编程</div></div><div class="AuthorInfo"><span class="AuthorInfo-name">张三</span></div>"#,
+    );
+    let answer = ZhihuAnswer::from_html(&html, RenderOptions::default().frontmatter(true)).unwrap();
+    assert_eq!(answer.topics(), ["编程", "Rust \"语言\""]);
+    let expected =
+        "---\ntitle: \"标题\"\nreading_minutes: 1\nauthor: \"张三\"\ntags: [\"编程\", \"Rust \\\"语言\\\"\"]\n---\n\n";
+    assert!(answer.to_string().starts_with(expected));
+    let options = RenderOptions::default().frontmatter(true).frontmatter_format(FrontmatterFormat::Toml);
+    let toml = ZhihuAnswer::from_html(&html, options).unwrap().to_string();
+    assert!(toml.contains("author = \"张三\"\ntags = [\"编程\", \"Rust \\\"语言\\\"\"]\n+++\n"));
+}
+
+#[test]
+fn anonymous_and_deleted_answers() {
+    let html = answer_page(r#"<p>匿名回答</p>"#).replace(
+        "<body>",
+        r#"<body><div class="AuthorInfo"><meta itemprop="name" content="匿名用户"><span class="AuthorInfo-name">匿名用户</span></div>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    assert_eq!(answer.author(), Some("匿名用户"));
+    assert!(answer.is_anonymous());
+    assert!(!answer.is_deleted());
+    assert_eq!(answer.to_string(), "# 标题\n\n匿名回答\n\n");
+    let byline = ZhihuAnswer::from_html(&html, RenderOptions::default().author_byline(true)).unwrap();
+    assert_eq!(byline.to_string(), "# 标题\n\n> 作者: 匿名用户\n\n匿名回答\n\n");
+    let deleted = r#"<h1 class="QuestionHeader-title">标题</h1><div class="AnswerItem"><span>该回答已被删除</span></div>"#;
+    let answer = ZhihuAnswer::from_html(deleted, RenderOptions::default().mode(ParseMode::Strict)).unwrap();
+    assert!(answer.is_deleted());
+    assert_eq!(answer.to_string(), "# 标题\n\n> 该回答已被删除\n\n");
+    // the notice of another answer or in the page data does not delete this one
+    let elsewhere = r#"<html><head><link rel="canonical" href="https://www.zhihu.com/question/1/answer/2"></head><body><h1 class="QuestionHeader-title">标题</h1><div class="AnswerItem" name="2"></div><div class="AnswerItem" name="3"><span>该回答已被删除</span></div><script>"该回答已被删除"</script></body></html>"#;
+    assert!(!ZhihuAnswer::from_str(elsewhere).unwrap().is_deleted());
+}
+
+#[test]
+fn clone_content() {
+    let answer = ZhihuAnswer::from_str(&answer_page("<p>缓存</p>")).unwrap();
+    let cached = answer.clone();
+    assert_eq!(cached.to_string(), answer.to_string());
+    let article = ZhihuArticle::from_str(&article_page("<p>缓存</p>")).unwrap();
+    assert_eq!(article.clone().permalink(), article.permalink());
+    let strict = RenderOptions::default().mode(ParseMode::Strict);
+    let error = ZhihuAnswer::from_html("<html></html>", strict).unwrap_err();
+    assert_eq!(error.clone(), ZhihuError::MissingContent("h1.QuestionHeader-title".to_string()));
+}
+
+#[test]
+fn classify_urls() {
+    use zhihu_link::{ContentKind, UrlDispatcher};
+    assert_eq!(
+        UrlDispatcher::classify("https://www.zhihu.com/question/347662352/answer/847873806?utm_source=wechat"),
+        Some(ContentKind::Answer { question: 347662352, answer: 847873806 })
+    );
+    assert_eq!(
+        UrlDispatcher::classify("https://www.zhihu.com/question/347662352"),
+        Some(ContentKind::Question { id: 347662352 })
+    );
+    assert_eq!(
+        UrlDispatcher::classify("https://zhuanlan.zhihu.com/p/620388361/"),
+        Some(ContentKind::Article { id: 620388361 })
+    );
+    assert_eq!(
+        UrlDispatcher::classify("https://www.bilibili.com/read/cv21789042"),
+        Some(ContentKind::BilibiliArticle { id: 21789042 })
+    );
+    assert_eq!(UrlDispatcher::classify("https://www.zhihu.com/people/aster"), None);
+    assert_eq!(UrlDispatcher::classify("not a url"), None);
+}
+
+#[test]
+fn comment_permission() {
+    let data = r#"{"initialState":{"entities":{"answers":{"1":{"commentPermission":"all","canComment":{"status":true}},"2":{"commentPermission":"followee","canComment":{"status":false,"reason":"仅关注者可评论"}}}}}}"#;
+    let page = |canonical: &str| {
+        answer_page("<p>正文</p>").replace(
+            "<html>",
+            &format!(r#"<html><head><link rel="canonical" href="{canonical}"><script id="js-initialData" type="text/json">{data}</script></head>"#),
+        )
+    };
+    let open = ZhihuAnswer::from_str(&page("https://www.zhihu.com/question/9/answer/1")).unwrap();
+    assert_eq!(open.comment_permission(), Some("all"));
+    assert!(!open.comments_closed());
+    let followee = ZhihuAnswer::from_str(&page("https://www.zhihu.com/question/9/answer/2")).unwrap();
+    assert_eq!(followee.comment_permission(), Some("followee"));
+    assert!(followee.comments_closed());
+    let unknown = ZhihuAnswer::from_str(&answer_page("<p>正文</p>")).unwrap();
+    assert_eq!(unknown.comment_permission(), None);
+    assert!(!unknown.comments_closed());
+}
+
+#[test]
+fn repost_forbidden() {
+    use zhihu_link::CopyrightPolicy;
+    let quoted = answer_page("<p>正文里提到禁止转载</p>");
+    assert!(!ZhihuAnswer::from_str(&quoted).unwrap().is_repost_forbidden());
+    let html = quoted.replace(
+        "</body>",
+        r#"<div class="ContentItem-actions"><span>著作权归作者所有，作者设置了禁止转载</span></div></body>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    assert!(answer.is_repost_forbidden());
+    assert!(!answer.to_string().contains("> 著作权归作者所有"));
+    let notice = ZhihuAnswer::from_html(&html, RenderOptions::default().copyright(CopyrightPolicy::Notice)).unwrap();
+    assert!(notice.to_string().starts_with("# 标题\n\n> 著作权归作者所有, 作者设置了禁止转载\n\n正文"));
+    let path = std::env::temp_dir().join(format!("zhihu-link-copyright-{}.md", std::process::id()));
+    let refuse = ZhihuAnswer::from_html(&html, RenderOptions::default().copyright(CopyrightPolicy::Refuse)).unwrap();
+    assert!(matches!(refuse.save(&path), Err(ZhihuError::RepostForbidden(_))));
+    assert!(!path.exists());
+}
+
+#[test]
+fn repost_forbidden_of_this_answer() {
+    // another answer of the page and the page data forbid reposting, this answer does not
+    let html = r#"<html><head><link rel="canonical" href="https://www.zhihu.com/question/1/answer/2"></head><body><h1 class="QuestionHeader-title">标题</h1><div class="ContentItem AnswerItem" name="2"><span class="CopyrightRichText-richText"><p>正文</p></span><div class="ContentItem-actions"><span>著作权归作者所有</span></div></div><div class="ContentItem AnswerItem" name="3"><span class="CopyrightRichText-richText"><p>另一个回答</p></span><div class="ContentItem-actions"><span>作者设置了禁止转载</span></div></div><script>var notice = "禁止转载";</script></body></html>"#;
+    assert!(!ZhihuAnswer::from_str(html).unwrap().is_repost_forbidden());
+    assert!(ZhihuAnswer::from_str(&html.replace("作者所有<", "作者所有, 禁止转载<")).unwrap().is_repost_forbidden());
+}
+
+#[test]
+fn provenance_comment() {
+    let html = article_page("<p>正文</p>");
+    let options = RenderOptions::default().frontmatter(true).provenance(true);
+    let generator = format!("generator: zhihu-link v{} -->", env!("CARGO_PKG_VERSION"));
+    // parsing html does not know when it was fetched
+    let parsed = ZhihuArticle::from_html(&html, options.clone()).unwrap().to_string();
+    let comment = parsed.split("---\n\n").nth(1).unwrap().lines().next().unwrap();
+    assert_eq!(comment, format!("<!-- source: https://zhuanlan.zhihu.com/p/620388361 {generator}"));
+    let article = ZhihuArticle::from_html(&html, options).unwrap().with_fetched_at(Some(1683356889)).to_string();
+    let comment = article.split("---\n\n").nth(1).unwrap().lines().next().unwrap();
+    assert_eq!(
+        comment,
+        format!("<!-- source: https://zhuanlan.zhihu.com/p/620388361 fetched: 2023-05-06T07:08:09Z {generator}")
+    );
+    assert!(article.contains(" -->\n\n# 专栏标题\n\n正文"));
+    assert!(!ZhihuArticle::from_str(&html).unwrap().to_string().contains("<!--"));
+}
+
+#[test]
+fn canonical_urls() {
+    use zhihu_link::{
+        ContentKind, UrlDispatcher,
+        utils::{answer_url, article_url, question_url},
+    };
+    assert_eq!(answer_url(1, 2), "https://www.zhihu.com/question/1/answer/2");
+    assert_eq!(article_url(3), "https://zhuanlan.zhihu.com/p/3");
+    assert_eq!(question_url(4), "https://www.zhihu.com/question/4");
+    // the urls classify back to their ids
+    assert_eq!(UrlDispatcher::classify(answer_url(1, 2).as_str()), Some(ContentKind::Answer { question: 1, answer: 2 }));
+    assert_eq!(UrlDispatcher::classify(article_url(3).as_str()), Some(ContentKind::Article { id: 3 }));
+    assert_eq!(UrlDispatcher::classify(question_url(4).as_str()), Some(ContentKind::Question { id: 4 }));
+}
+
+#[test]
+fn edit_note() {
+    let html = answer_page("<p>正文</p>").replace(
+        "</body>",
+        r#"<div class="ContentItem-time"><a href="/question/1/answer/2"><span data-tooltip="发布于 2021-01-02 03:04">编辑于 2023-05-06 07:08</span></a></div></body>"#,
+    );
+    let answer = ZhihuAnswer::from_str(&html).unwrap();
+    assert_eq!(answer.edited_at(), Some("2023-05-06 07:08"));
+    assert!(!answer.to_string().contains("编辑于"));
+    let noted = ZhihuAnswer::from_html(&html, RenderOptions::default().edit_note(true)).unwrap().to_string();
+    assert!(noted.contains("正文\n\n*编辑于 2023-05-06 07:08*\n"));
+    let published = html.replace("编辑于 2023-05-06 07:08", "发布于 2021-01-02 03:04");
+    assert_eq!(ZhihuAnswer::from_str(&published).unwrap().edited_at(), None);
+}
+
+#[test]
+fn content_times() {
+    let html = article_page("<p>正文</p>").replace(
+        "</head>",
+        r#"<meta itemprop="datePublished" content="2021-01-02T03:04:05.000Z"><meta itemprop="dateModified" content="2023-05-06T07:08:09.000Z"></head>"#,
+    );
+    let article = ZhihuArticle::from_html(&html, RenderOptions::default().frontmatter(true)).unwrap();
+    assert_eq!(article.published_at(), Some("2021-01-02T03:04:05.000Z"));
+    assert_eq!(article.edited_at(), Some("2023-05-06T07:08:09.000Z"));
+    let markdown = article.to_string();
+    assert!(markdown.contains("date: \"2021-01-02T03:04:05.000Z\"\nupdated: \"2023-05-06T07:08:09.000Z\"\n"));
+    // TOML has native datetimes
+    let options = RenderOptions::default().frontmatter(true).frontmatter_format(FrontmatterFormat::Toml);
+    let toml = ZhihuArticle::from_html(&html, options).unwrap().to_string();
+    assert!(toml.contains("date = 2021-01-02T03:04:05.000Z\nupdated = 2023-05-06T07:08:09.000Z\n"));
+    // a modification at the moment of creation is no edit
+    let unedited = html.replace("2023-05-06T07:08:09.000Z", "2021-01-02T03:04:05.000Z");
+    assert_eq!(ZhihuArticle::from_str(&unedited).unwrap().edited_at(), None);
+    // answers show the times in a line under the content
+    let page = answer_page("<p>正文</p>")
+        .replace("</body>", r#"<div class="ContentItem-time"><a><span>发布于 2022-03-04 05:06</span></a></div></body>"#);
+    let answer = ZhihuAnswer::from_str(&page).unwrap();
+    assert_eq!(answer.published_at(), Some("2022-03-04 05:06"));
+    let options = RenderOptions::default().frontmatter(true).frontmatter_format(FrontmatterFormat::Toml);
+    assert!(ZhihuAnswer::from_html(&page, options).unwrap().to_string().contains("date = \"2022-03-04 05:06\"\n"));
+    assert_eq!(answer.edited_at(), None);
+}
+
+#[test]
+fn content_html() {
+    let html =
+        answer_page(r#"<p>正文<a href="https://example.com">链接</a></p><div class="ad">广告</div><ul><li>一</li></ul>"#);
+    let options = RenderOptions::default().remove_selectors(vec![Selector::new(".ad")]);
+    let answer = ZhihuAnswer::from_html(&html, options).unwrap();
+    assert_eq!(answer.content_html(), r#"<p>正文<a href="https://example.com">链接</a></p><ul><li>一</li></ul>"#);
+    let article = ZhihuArticle::from_str(&article_page("<p>正文</p><figure><img src=\"a.png\"></figure>")).unwrap();
+    assert_eq!(article.content_html(), r#"<p>正文</p><figure><img src="a.png"></figure>"#);
+}
+
+#[test]
+fn title_fallback() {
+    let page =
+        |head: &str| format!(r#"<html><head>{head}</head><body><div class="Post-RichText"><p>正文</p></div></body></html>"#);
+    let article = ZhihuArticle::from_str(&page("<title>标签里的标题 - 知乎</title>")).unwrap();
+    assert!(article.to_string().starts_with("# 标签里的标题\n\n正文"));
+    let og = page(r#"<meta property="og:title" content="分享标题"><title>标签里的标题 - 知乎</title>"#);
+    assert!(ZhihuArticle::from_str(&og).unwrap().to_string().starts_with("# 分享标题\n\n"));
+    assert_eq!(ZhihuArticle::from_str(&page("")).unwrap().to_string(), "正文\n\n");
+    assert!(ZhihuArticle::from_html(&page(""), RenderOptions::default().mode(ParseMode::Strict)).is_err());
+}
+
+#[test]
+fn article_column() {
+    let html = article_page("<p>正文</p>").replace(
+        "<h1",
+        r#"<div class="Post-Header"><a class="ColumnLink" href="//zhuanlan.zhihu.com/c_1234"><div class="ColumnLink-title">编程随想</div></a></div><h1"#,
+    );
+    let article = ZhihuArticle::from_html(&html, RenderOptions::default().frontmatter(true)).unwrap();
+    let column = article.column().unwrap();
+    assert_eq!(column.name, "编程随想");
+    assert_eq!(column.url, "https://zhuanlan.zhihu.com/c_1234");
+    assert!(article.to_string().contains("column: \"编程随想\"\ncolumn_url: \"https://zhuanlan.zhihu.com/c_1234\"\n"));
+    assert!(ZhihuArticle::from_str(&article_page("<p>正文</p>")).unwrap().column().is_none());
+}
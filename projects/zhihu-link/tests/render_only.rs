@@ -0,0 +1,33 @@
+//! Runs with and without the `network` feature: `cargo test --no-default-features --test render_only`.
+
+use std::str::FromStr;
+use zhihu_link::{AnswerPage, RenderOptions, ZhihuAnswer, ZhihuArticle};
+
+#[test]
+fn render_answer_from_html() {
+    let html =
+        r#"<h1 class="QuestionHeader-title">标题</h1><span class="CopyrightRichText-richText"><p>正文<b>加粗</b></p></span>"#;
+    let answer = ZhihuAnswer::from_str(html).unwrap();
+    assert!(answer.to_string().starts_with("# 标题\n\n正文**加粗**"));
+}
+
+#[test]
+fn skip_html_comments() {
+    let html =
+        r#"<h1 class="QuestionHeader-title">标题</h1><span class="CopyrightRichText-richText"><p>a<!-- c -->b</p></span>"#;
+    assert!(ZhihuAnswer::from_str(html).unwrap().to_string().starts_with("# 标题\n\nab"));
+}
+
+#[test]
+fn render_article_from_html() {
+    let html = r#"<h1 class="Post-Title">标题</h1><div class="Post-RichText"><ul><li>一</li></ul></div>"#;
+    let article = ZhihuArticle::from_html(html, RenderOptions::default().frontmatter(true)).unwrap();
+    assert!(article.to_string().contains("# 标题\n\n- 一\n"));
+}
+
+#[test]
+fn parse_answer_page() {
+    let page = AnswerPage::from_json(r#"{"data":[{"target":{"type":"answer","id":7}}],"paging":{"is_end":true}}"#).unwrap();
+    assert_eq!(page.answers(), [7]);
+    assert_eq!(page.next_cursor(), None);
+}
@@ -0,0 +1,14 @@
+//! Check for the target with `cargo build --no-default-features --features wasm --target wasm32-unknown-unknown`.
+
+use zhihu_link::render;
+
+#[test]
+fn render_article_or_answer() {
+    let article = r#"<h1 class="Post-Title">文章</h1><div class="Post-RichText"><p>正文</p></div>"#;
+    assert_eq!(render(article).unwrap(), "# 文章\n\n正文\n\n");
+    let answer = r#"<h1 class="QuestionHeader-title">问题</h1><span class="CopyrightRichText-richText"><p>回答</p></span>"#;
+    assert_eq!(render(answer).unwrap(), "# 问题\n\n回答\n\n");
+    // markup the strict mode does not know still makes an article
+    let article = r#"<h1 class="Post-Title">文章</h1><div class="Post-RichText"><div><p>正文</p></div></div>"#;
+    assert_eq!(render(article).unwrap(), "# 文章\n\n正文\n\n");
+}